@@ -1,10 +1,10 @@
 use artifact::indexer::{Indexer};
 
 fn main() {
-    let indexer = Indexer::new().expect("Failed to create Indexer");
+    let mut indexer = Indexer::new().expect("Failed to create Indexer");
 
-    match indexer.log_blocks_and_txids() {
-        Ok(_) => println!("Finished logging blocks and transaction IDs."),
-        Err(e) => eprintln!("Error while logging blocks and transaction IDs: {:?}", e),
+    // Follows the tip until an error stops it
+    if let Err(e) = indexer.follow() {
+        eprintln!("Error while indexing blocks: [{}] {}", e.code(), e);
     }
 }
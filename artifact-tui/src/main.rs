@@ -1,3 +1,6 @@
+use artifact::establish_connection;
+use artifact::models::balance::fetch_balances_with_divisibility;
+use artifact::models::token::format_quantity;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
@@ -6,11 +9,14 @@ use crossterm::{
 use std::{io, thread, time::Duration};
 use tui::{
     backend::CrosstermBackend,
-    widgets::{Block, Borders},
+    widgets::{Block, Borders, List, ListItem},
     Terminal,
 };
 
 fn main() -> Result<(), io::Error> {
+    let conn = &mut establish_connection().expect("Error connecting to database");
+    let balances = fetch_balances_with_divisibility(conn).expect("Error loading balances");
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -20,8 +26,19 @@ fn main() -> Result<(), io::Error> {
 
     terminal.draw(|f| {
         let size = f.size();
+        let items: Vec<ListItem> = balances
+            .iter()
+            .map(|(balance, divisibility)| {
+                ListItem::new(format!(
+                    "{} {} {}",
+                    balance.token,
+                    balance.address,
+                    format_quantity(balance.quantity as u64, *divisibility as u8)
+                ))
+            })
+            .collect();
         let block = Block::default().title("Artifact").borders(Borders::ALL);
-        f.render_widget(block, size);
+        f.render_widget(List::new(items).block(block), size);
     })?;
 
     thread::sleep(Duration::from_millis(5000));
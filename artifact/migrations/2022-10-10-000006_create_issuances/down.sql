@@ -0,0 +1 @@
+DROP TABLE issuances;
\ No newline at end of file
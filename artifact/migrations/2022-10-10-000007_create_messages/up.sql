@@ -0,0 +1,13 @@
+CREATE TABLE messages (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  tx_index INTEGER NOT NULL CHECK(tx_index >= 0),
+  txid TEXT NOT NULL,
+  source TEXT NOT NULL,
+  destination TEXT,
+  message_type INTEGER NOT NULL,
+  data BLOB NOT NULL
+);
+
+CREATE INDEX ix_messages_block_height ON messages (block_height);
+CREATE INDEX ix_messages_txid ON messages (txid);
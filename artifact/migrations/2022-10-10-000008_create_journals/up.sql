@@ -0,0 +1,40 @@
+DROP TABLE credits;
+DROP TABLE debits;
+
+CREATE TABLE credits (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  address TEXT NOT NULL,
+  token TEXT NOT NULL,
+  quantity INTEGER NOT NULL DEFAULT 0 CHECK(quantity >= 0 AND quantity <= 10000),
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  tx_index INTEGER NOT NULL CHECK(tx_index >= 0),
+  txid TEXT NOT NULL,
+  event TEXT NOT NULL,
+  message_id INTEGER NOT NULL,
+  FOREIGN KEY (address) REFERENCES addresses(address),
+  FOREIGN KEY (token) REFERENCES tokens(token),
+  FOREIGN KEY (message_id) REFERENCES messages(id)
+);
+
+CREATE INDEX ix_credits_address ON credits (address);
+CREATE INDEX ix_credits_token ON credits (token);
+CREATE INDEX ix_credits_block_height ON credits (block_height);
+
+CREATE TABLE debits (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  address TEXT NOT NULL,
+  token TEXT NOT NULL,
+  quantity INTEGER NOT NULL DEFAULT 0 CHECK(quantity >= 0 AND quantity <= 10000),
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  tx_index INTEGER NOT NULL CHECK(tx_index >= 0),
+  txid TEXT NOT NULL,
+  event TEXT NOT NULL,
+  message_id INTEGER NOT NULL,
+  FOREIGN KEY (address) REFERENCES addresses(address),
+  FOREIGN KEY (token) REFERENCES tokens(token),
+  FOREIGN KEY (message_id) REFERENCES messages(id)
+);
+
+CREATE INDEX ix_debits_address ON debits (address);
+CREATE INDEX ix_debits_token ON debits (token);
+CREATE INDEX ix_debits_block_height ON debits (block_height);
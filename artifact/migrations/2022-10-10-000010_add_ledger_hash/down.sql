@@ -0,0 +1 @@
+ALTER TABLE blocks DROP COLUMN ledger_hash;
\ No newline at end of file
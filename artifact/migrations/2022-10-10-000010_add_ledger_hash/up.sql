@@ -0,0 +1 @@
+ALTER TABLE blocks ADD COLUMN ledger_hash TEXT NOT NULL DEFAULT '';
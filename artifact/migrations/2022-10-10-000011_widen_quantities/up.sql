@@ -0,0 +1,66 @@
+ALTER TABLE tokens ADD COLUMN divisibility INTEGER NOT NULL DEFAULT 0 CHECK(divisibility >= 0 AND divisibility <= 8);
+
+ALTER TABLE issuances ADD COLUMN quantity BIGINT NOT NULL DEFAULT 0 CHECK(quantity >= 0);
+ALTER TABLE issuances ADD COLUMN divisibility INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE balances_new (
+  address TEXT NOT NULL,
+  token TEXT NOT NULL,
+  quantity BIGINT NOT NULL DEFAULT 0 CHECK(quantity >= 0),
+  PRIMARY KEY (address, token),
+  FOREIGN KEY (address) REFERENCES addresses(address),
+  FOREIGN KEY (token) REFERENCES tokens(token)
+);
+
+INSERT INTO balances_new SELECT * FROM balances;
+DROP TABLE balances;
+ALTER TABLE balances_new RENAME TO balances;
+
+CREATE INDEX ix_balances_address ON balances (address);
+CREATE INDEX ix_balances_token ON balances (token);
+
+CREATE TABLE credits_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  address TEXT NOT NULL,
+  token TEXT NOT NULL,
+  quantity BIGINT NOT NULL DEFAULT 0 CHECK(quantity >= 0),
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  tx_index INTEGER NOT NULL CHECK(tx_index >= 0),
+  txid TEXT NOT NULL,
+  event TEXT NOT NULL,
+  message_id INTEGER NOT NULL,
+  FOREIGN KEY (address) REFERENCES addresses(address),
+  FOREIGN KEY (token) REFERENCES tokens(token),
+  FOREIGN KEY (message_id) REFERENCES messages(id)
+);
+
+INSERT INTO credits_new SELECT * FROM credits;
+DROP TABLE credits;
+ALTER TABLE credits_new RENAME TO credits;
+
+CREATE INDEX ix_credits_address ON credits (address);
+CREATE INDEX ix_credits_token ON credits (token);
+CREATE INDEX ix_credits_block_height ON credits (block_height);
+
+CREATE TABLE debits_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  address TEXT NOT NULL,
+  token TEXT NOT NULL,
+  quantity BIGINT NOT NULL DEFAULT 0 CHECK(quantity >= 0),
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  tx_index INTEGER NOT NULL CHECK(tx_index >= 0),
+  txid TEXT NOT NULL,
+  event TEXT NOT NULL,
+  message_id INTEGER NOT NULL,
+  FOREIGN KEY (address) REFERENCES addresses(address),
+  FOREIGN KEY (token) REFERENCES tokens(token),
+  FOREIGN KEY (message_id) REFERENCES messages(id)
+);
+
+INSERT INTO debits_new SELECT * FROM debits;
+DROP TABLE debits;
+ALTER TABLE debits_new RENAME TO debits;
+
+CREATE INDEX ix_debits_address ON debits (address);
+CREATE INDEX ix_debits_token ON debits (token);
+CREATE INDEX ix_debits_block_height ON debits (block_height);
@@ -0,0 +1,20 @@
+ALTER TABLE tokens ADD COLUMN owner TEXT;
+ALTER TABLE tokens ADD COLUMN issuer TEXT;
+ALTER TABLE tokens ADD COLUMN issued_at_height INTEGER;
+ALTER TABLE tokens ADD COLUMN issuance_txid TEXT;
+ALTER TABLE tokens ADD COLUMN total_supply BIGINT NOT NULL DEFAULT 0 CHECK(total_supply >= 0);
+
+CREATE INDEX ix_tokens_owner ON tokens (owner);
+
+CREATE TABLE ownership_transfers (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  token TEXT NOT NULL,
+  source TEXT NOT NULL,
+  destination TEXT NOT NULL,
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  txid TEXT NOT NULL,
+  FOREIGN KEY (token) REFERENCES tokens(token)
+);
+
+CREATE INDEX ix_ownership_transfers_token ON ownership_transfers (token);
+CREATE INDEX ix_ownership_transfers_block_height ON ownership_transfers (block_height);
@@ -0,0 +1,3 @@
+DROP INDEX ix_addresses_unlock_height;
+
+ALTER TABLE addresses DROP COLUMN unlock_height;
\ No newline at end of file
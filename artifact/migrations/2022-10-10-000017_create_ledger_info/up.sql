@@ -0,0 +1,5 @@
+CREATE TABLE ledger_info (
+  id INTEGER PRIMARY KEY NOT NULL CHECK(id = 1),
+  schema_version TEXT NOT NULL,
+  protocol_version INTEGER NOT NULL CHECK(protocol_version >= 0)
+);
@@ -0,0 +1,2 @@
+-- Reserved names now come from the network genesis
+DELETE FROM tokens WHERE token IN ('A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9') AND owner IS NULL;
@@ -0,0 +1,13 @@
+CREATE TABLE address_events (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  address TEXT NOT NULL,
+  event TEXT NOT NULL,
+  flags INTEGER NOT NULL,
+  unlock_height INTEGER,
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  txid TEXT,
+  FOREIGN KEY (address) REFERENCES addresses(address)
+);
+
+CREATE INDEX ix_address_events_address ON address_events (address);
+CREATE INDEX ix_address_events_block_height ON address_events (block_height);
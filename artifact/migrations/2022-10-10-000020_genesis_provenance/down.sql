@@ -0,0 +1,46 @@
+CREATE TABLE credits_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  address TEXT NOT NULL,
+  token TEXT NOT NULL,
+  quantity BIGINT NOT NULL DEFAULT 0 CHECK(quantity >= 0),
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  tx_index INTEGER NOT NULL CHECK(tx_index >= 0),
+  txid TEXT NOT NULL,
+  event TEXT NOT NULL,
+  message_id INTEGER NOT NULL,
+  memo TEXT,
+  FOREIGN KEY (address) REFERENCES addresses(address),
+  FOREIGN KEY (token) REFERENCES tokens(token),
+  FOREIGN KEY (message_id) REFERENCES messages(id)
+);
+
+INSERT INTO credits_new SELECT * FROM credits WHERE message_id IS NOT NULL;
+DROP TABLE credits;
+ALTER TABLE credits_new RENAME TO credits;
+
+CREATE INDEX ix_credits_address ON credits (address);
+CREATE INDEX ix_credits_token ON credits (token);
+CREATE INDEX ix_credits_block_height ON credits (block_height);
+
+CREATE TABLE debits_new (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  address TEXT NOT NULL,
+  token TEXT NOT NULL,
+  quantity BIGINT NOT NULL DEFAULT 0 CHECK(quantity >= 0),
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  tx_index INTEGER NOT NULL CHECK(tx_index >= 0),
+  txid TEXT NOT NULL,
+  event TEXT NOT NULL,
+  message_id INTEGER NOT NULL,
+  FOREIGN KEY (address) REFERENCES addresses(address),
+  FOREIGN KEY (token) REFERENCES tokens(token),
+  FOREIGN KEY (message_id) REFERENCES messages(id)
+);
+
+INSERT INTO debits_new SELECT * FROM debits WHERE message_id IS NOT NULL;
+DROP TABLE debits;
+ALTER TABLE debits_new RENAME TO debits;
+
+CREATE INDEX ix_debits_address ON debits (address);
+CREATE INDEX ix_debits_token ON debits (token);
+CREATE INDEX ix_debits_block_height ON debits (block_height);
@@ -0,0 +1 @@
+ALTER TABLE ledger_info DROP COLUMN network;
@@ -0,0 +1,2 @@
+-- Network the ledger was indexed on, set by the first sync
+ALTER TABLE ledger_info ADD COLUMN network TEXT;
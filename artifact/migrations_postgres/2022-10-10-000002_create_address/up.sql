@@ -0,0 +1,4 @@
+CREATE TABLE addresses (
+  address TEXT COLLATE "C" PRIMARY KEY NOT NULL,
+  flags INTEGER NOT NULL DEFAULT 0 CHECK(flags >= 0)
+);
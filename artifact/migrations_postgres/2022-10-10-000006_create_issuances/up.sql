@@ -0,0 +1,12 @@
+CREATE TABLE issuances (
+  id SERIAL PRIMARY KEY NOT NULL,
+  token TEXT COLLATE "C" NOT NULL,
+  issuer TEXT COLLATE "C" NOT NULL,
+  flags INTEGER NOT NULL DEFAULT 0 CHECK(flags >= 0),
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  txid TEXT NOT NULL,
+  FOREIGN KEY (token) REFERENCES tokens(token)
+);
+
+CREATE INDEX ix_issuances_token ON issuances (token);
+CREATE INDEX ix_issuances_issuer ON issuances (issuer);
@@ -0,0 +1,5 @@
+CREATE TABLE blocks (
+  height INTEGER PRIMARY KEY NOT NULL CHECK(height >= 0),
+  hash TEXT NOT NULL UNIQUE,
+  prev_hash TEXT NOT NULL
+);
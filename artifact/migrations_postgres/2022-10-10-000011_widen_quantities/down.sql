@@ -0,0 +1,16 @@
+ALTER TABLE balances DROP CONSTRAINT balances_quantity_check;
+ALTER TABLE balances ALTER COLUMN quantity TYPE INTEGER;
+ALTER TABLE balances ADD CONSTRAINT balances_quantity_check CHECK(quantity >= 0 AND quantity <= 10000);
+
+ALTER TABLE credits DROP CONSTRAINT credits_quantity_check;
+ALTER TABLE credits ALTER COLUMN quantity TYPE INTEGER;
+ALTER TABLE credits ADD CONSTRAINT credits_quantity_check CHECK(quantity >= 0 AND quantity <= 10000);
+
+ALTER TABLE debits DROP CONSTRAINT debits_quantity_check;
+ALTER TABLE debits ALTER COLUMN quantity TYPE INTEGER;
+ALTER TABLE debits ADD CONSTRAINT debits_quantity_check CHECK(quantity >= 0 AND quantity <= 10000);
+
+ALTER TABLE issuances DROP COLUMN divisibility;
+ALTER TABLE issuances DROP COLUMN quantity;
+
+ALTER TABLE tokens DROP COLUMN divisibility;
@@ -0,0 +1,16 @@
+ALTER TABLE tokens ADD COLUMN divisibility INTEGER NOT NULL DEFAULT 0 CHECK(divisibility >= 0 AND divisibility <= 8);
+
+ALTER TABLE issuances ADD COLUMN quantity BIGINT NOT NULL DEFAULT 0 CHECK(quantity >= 0);
+ALTER TABLE issuances ADD COLUMN divisibility INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE balances DROP CONSTRAINT balances_quantity_check;
+ALTER TABLE balances ALTER COLUMN quantity TYPE BIGINT;
+ALTER TABLE balances ADD CONSTRAINT balances_quantity_check CHECK(quantity >= 0);
+
+ALTER TABLE credits DROP CONSTRAINT credits_quantity_check;
+ALTER TABLE credits ALTER COLUMN quantity TYPE BIGINT;
+ALTER TABLE credits ADD CONSTRAINT credits_quantity_check CHECK(quantity >= 0);
+
+ALTER TABLE debits DROP CONSTRAINT debits_quantity_check;
+ALTER TABLE debits ALTER COLUMN quantity TYPE BIGINT;
+ALTER TABLE debits ADD CONSTRAINT debits_quantity_check CHECK(quantity >= 0);
@@ -0,0 +1,9 @@
+DROP TABLE ownership_transfers;
+
+DROP INDEX ix_tokens_owner;
+
+ALTER TABLE tokens DROP COLUMN total_supply;
+ALTER TABLE tokens DROP COLUMN issuance_txid;
+ALTER TABLE tokens DROP COLUMN issued_at_height;
+ALTER TABLE tokens DROP COLUMN issuer;
+ALTER TABLE tokens DROP COLUMN owner;
@@ -0,0 +1,3 @@
+DROP INDEX ix_tokens_parent;
+
+ALTER TABLE tokens DROP COLUMN parent;
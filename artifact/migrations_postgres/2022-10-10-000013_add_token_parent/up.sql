@@ -0,0 +1,3 @@
+ALTER TABLE tokens ADD COLUMN parent TEXT COLLATE "C" REFERENCES tokens(token);
+
+CREATE INDEX ix_tokens_parent ON tokens (parent);
@@ -0,0 +1 @@
+ALTER TABLE credits DROP COLUMN memo;
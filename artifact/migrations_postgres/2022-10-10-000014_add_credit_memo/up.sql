@@ -0,0 +1 @@
+ALTER TABLE credits ADD COLUMN memo TEXT;
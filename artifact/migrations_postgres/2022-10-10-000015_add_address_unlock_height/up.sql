@@ -0,0 +1,3 @@
+ALTER TABLE addresses ADD COLUMN unlock_height INTEGER CHECK(unlock_height >= 0);
+
+CREATE INDEX ix_addresses_unlock_height ON addresses (unlock_height);
@@ -0,0 +1,18 @@
+CREATE TABLE rejected_messages (
+  id SERIAL PRIMARY KEY NOT NULL,
+  block_height INTEGER NOT NULL CHECK(block_height >= 0),
+  tx_index INTEGER NOT NULL CHECK(tx_index >= 0),
+  txid TEXT NOT NULL,
+  source TEXT COLLATE "C",
+  destination TEXT COLLATE "C",
+  message_type INTEGER,
+  reason_code INTEGER NOT NULL,
+  reason TEXT NOT NULL,
+  message_id INTEGER,
+  FOREIGN KEY (message_id) REFERENCES messages(id)
+);
+
+CREATE INDEX ix_rejected_messages_block_height ON rejected_messages (block_height);
+CREATE INDEX ix_rejected_messages_txid ON rejected_messages (txid);
+CREATE INDEX ix_rejected_messages_source ON rejected_messages (source);
+CREATE INDEX ix_rejected_messages_destination ON rejected_messages (destination);
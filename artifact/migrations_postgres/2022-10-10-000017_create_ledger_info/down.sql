@@ -0,0 +1 @@
+DROP TABLE ledger_info;
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE address_events;
\ No newline at end of file
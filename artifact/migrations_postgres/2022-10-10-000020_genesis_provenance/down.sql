@@ -0,0 +1,4 @@
+DELETE FROM credits WHERE message_id IS NULL;
+DELETE FROM debits WHERE message_id IS NULL;
+ALTER TABLE credits ALTER COLUMN message_id SET NOT NULL;
+ALTER TABLE debits ALTER COLUMN message_id SET NOT NULL;
@@ -0,0 +1,3 @@
+-- Genesis allocations are journaled without a message
+ALTER TABLE credits ALTER COLUMN message_id DROP NOT NULL;
+ALTER TABLE debits ALTER COLUMN message_id DROP NOT NULL;
@@ -1,16 +1,19 @@
 use artifact::models::address::*;
+use artifact::options::BitcoinRpcOptions;
 use artifact::*;
 use std::io::stdin;
 
 fn main() {
-    let connection = &mut establish_connection();
+    let connection = &mut establish_connection().expect("Error connecting to database");
+    let network = BitcoinRpcOptions::new().expect("Invalid Bitcoin options").network;
 
     let mut address = String::new();
 
     println!("What would you like your address to be?");
     stdin().read_line(&mut address).unwrap();
     let address = address.trim_end();
+    validate_address(address, network).expect("Invalid address");
 
-    create_address(connection, address, &0);
+    create_address(connection, address, &0).expect("Error saving new address");
     println!("\nSaved address {}", address);
 }
@@ -1,15 +1,20 @@
+use artifact::models::address::validate_address;
 use artifact::models::balance::*;
+use artifact::models::token::{fetch_token, parse_quantity};
+use artifact::options::BitcoinRpcOptions;
 use artifact::*;
 use std::io::stdin;
 
 fn main() {
-    let connection = &mut establish_connection();
+    let connection = &mut establish_connection().expect("Error connecting to database");
+    let network = BitcoinRpcOptions::new().expect("Invalid Bitcoin options").network;
 
     let mut address = String::new();
 
     println!("What would you like your address to be?");
     stdin().read_line(&mut address).unwrap();
     let address = address.trim_end();
+    validate_address(address, network).expect("Invalid address");
 
     let mut token = String::new();
 
@@ -17,6 +22,17 @@ fn main() {
     stdin().read_line(&mut token).unwrap();
     let token = token.trim_end().to_uppercase();
 
-    create_balance(connection, address, &token, &1);
+    let divisibility = fetch_token(connection, &token)
+        .expect("Error loading token")
+        .expect("Token not found")
+        .divisibility;
+
+    let mut quantity = String::new();
+
+    println!("What quantity should it hold?");
+    stdin().read_line(&mut quantity).unwrap();
+    let quantity = parse_quantity(quantity.trim_end(), divisibility as u8).unwrap();
+
+    create_balance(connection, address, &token, &(quantity as i64)).expect("Error saving new balance");
     println!("\nSaved balance {}", address);
 }
@@ -3,7 +3,7 @@ use artifact::*;
 use std::io::stdin;
 
 fn main() {
-    let connection = &mut establish_connection();
+    let connection = &mut establish_connection().expect("Error connecting to database");
 
     let mut token = String::new();
 
@@ -11,8 +11,14 @@ fn main() {
     stdin().read_line(&mut token).unwrap();
     let token = token.trim_end().to_uppercase();
 
+    let mut divisibility = String::new();
+
+    println!("How many decimal places should it have?");
+    stdin().read_line(&mut divisibility).unwrap();
+    let divisibility: i32 = divisibility.trim_end().parse().unwrap();
+
     let flags = Flags::LOCKED | Flags::NAMESPACE;
 
-    create_token(connection, &token, &flags.bits());
+    create_token(connection, &token, &flags.bits(), &divisibility).expect("Error saving new token");
     println!("\nSaved token {}", token);
 }
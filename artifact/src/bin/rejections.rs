@@ -0,0 +1,26 @@
+use artifact::models::rejection::*;
+use artifact::*;
+use std::io::stdin;
+
+fn main() {
+    let connection = &mut establish_connection().expect("Error connecting to database");
+
+    let mut query = String::new();
+
+    println!("What txid or address would you like rejections for?");
+    stdin().read_line(&mut query).unwrap();
+    let query = query.trim_end();
+
+    let mut results = fetch_rejections_by_txid(connection, query).expect("Error loading rejections");
+    if results.is_empty() {
+        results = fetch_rejections_by_address(connection, query).expect("Error loading rejections");
+    }
+
+    println!("Displaying {} rejections", results.len());
+    for rejection in results {
+        println!(
+            "{}:{} {} [{}] {}",
+            rejection.block_height, rejection.tx_index, rejection.txid, rejection.reason_code, rejection.reason
+        );
+    }
+}
@@ -1,11 +1,10 @@
 use artifact::models::balance::*;
-use artifact::schema::balances::dsl::*;
+use artifact::models::token::{fetch_token, format_quantity};
 use artifact::*;
-use diesel::prelude::*;
 use std::io::stdin;
 
 fn main() {
-    let connection = &mut establish_connection();
+    let connection = &mut establish_connection().expect("Error connecting to database");
 
     let mut query = String::new();
 
@@ -13,13 +12,19 @@ fn main() {
     stdin().read_line(&mut query).unwrap();
     let query = query.trim_end();
 
-    let results = balances
-        .filter(token.eq(query))
-        .load::<Balance>(connection)
-        .expect("Error loading balances");
+    let divisibility = fetch_token(connection, query)
+        .expect("Error loading token")
+        .expect("Token not found")
+        .divisibility;
+
+    let results = fetch_token_balances(connection, query).expect("Error loading balances");
 
     println!("Displaying {} balances", results.len());
     for balance in results {
-        println!("{} {}", balance.address, balance.quantity);
+        println!(
+            "{} {}",
+            balance.address,
+            format_quantity(balance.quantity as u64, divisibility as u8)
+        );
     }
 }
@@ -3,7 +3,7 @@ use artifact::*;
 use std::io::stdin;
 
 fn main() {
-    let connection = &mut establish_connection();
+    let connection = &mut establish_connection().expect("Error connecting to database");
 
     let mut token = String::new();
 
@@ -17,6 +17,14 @@ fn main() {
     stdin().read_line(&mut flags).unwrap();
     let flags: i32 = flags.trim_end().parse().unwrap();
 
-    update_token(connection, &token, &flags);
+    let current = fetch_token(connection, &token)
+        .expect("Error loading token")
+        .expect("Token not found");
+    if let Err(e) = validate_flags(&token, current.flags, flags) {
+        println!("\n{}", e);
+        return;
+    }
+
+    update_token(connection, &token, &flags).expect("Error updating token");
     println!("\nUpdated token {}", token);
 }
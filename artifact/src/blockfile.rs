@@ -0,0 +1,394 @@
+use crate::error::ArtifactError;
+use crate::source::{BlockSource, Tip};
+use bitcoin::block::Header;
+use bitcoin::consensus::deserialize;
+use bitcoin::hashes::Hash;
+use bitcoin::pow::Work;
+use bitcoin::{Block, BlockHash, Network};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Where a block's bytes start in the `blk*.dat` files
+#[derive(Clone, Copy)]
+struct Location {
+    file: u32,
+    offset: u64,
+    size: u32,
+}
+
+/// Block File Reader
+///
+/// Reads blocks straight out of bitcoind's `blocks/` directory. Opening
+/// scans every `blk*.dat` header and rebuilds the most-work chain, since
+/// blocks are stored in download order rather than height order. Headers
+/// aren't validated here, so `truncate_to` the node's best chain before
+/// indexing. Files written by Bitcoin Core 28+ are obfuscated with the key
+/// in `xor.dat`.
+pub struct BlockFileReader {
+    dir: PathBuf,
+    xor_key: [u8; 8],
+    chain: Vec<BlockHash>,
+    locations: HashMap<BlockHash, Location>,
+}
+
+impl BlockFileReader {
+    pub fn open(dir: &Path, network: Network) -> Result<Self, ArtifactError> {
+        let xor_key = read_xor_key(dir)?;
+        let magic = network.magic().to_bytes();
+
+        let mut headers = vec![];
+        let mut locations = HashMap::new();
+        for file in 0.. {
+            let path = block_file_path(dir, file);
+            if !path.exists() {
+                break;
+            }
+
+            for (header, location) in scan_file(&path, file, &magic, &xor_key)? {
+                locations.insert(header.block_hash(), location);
+                headers.push(header);
+            }
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            xor_key,
+            chain: best_chain(&headers),
+            locations,
+        })
+    }
+
+    /// Drop the blocks past where the chain leaves `node`'s best chain, so
+    /// only blocks the node has validated are read
+    pub fn truncate_to(&mut self, node: &dyn BlockSource) -> Result<(), ArtifactError> {
+        // Matching heights form a prefix, each hash commits to its ancestors
+        let (mut low, mut high) = (0, self.chain.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            if node.block_hash(mid as u32)? == Some(self.chain[mid]) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        self.chain.truncate(low);
+
+        Ok(())
+    }
+
+    /// The first `len` bytes of `hash`'s record, decoded
+    fn read<T: bitcoin::consensus::Decodable>(&self, hash: &BlockHash, len: Option<usize>) -> Result<T, ArtifactError> {
+        let location = self
+            .locations
+            .get(hash)
+            .ok_or_else(|| ArtifactError::BlockFile(format!("Block {} is not in {}", hash, self.dir.display())))?;
+
+        let path = block_file_path(&self.dir, location.file);
+        let mut file = File::open(&path).map_err(|e| io_error(&path, e))?;
+        let bytes = read_at(&mut file, location.offset, len.unwrap_or(location.size as usize), &self.xor_key)
+            .map_err(|e| io_error(&path, e))?;
+
+        deserialize(&bytes)
+            .map_err(|e| ArtifactError::BlockFile(format!("{} at {}: {}", path.display(), location.offset, e)))
+    }
+}
+
+impl BlockSource for BlockFileReader {
+    fn tip(&self) -> Result<Tip, ArtifactError> {
+        match self.chain.last() {
+            Some(hash) => Ok(Tip {
+                height: self.chain.len() as u32 - 1,
+                hash: *hash,
+            }),
+            None => Err(ArtifactError::BlockFile(format!("No blocks in {}", self.dir.display()))),
+        }
+    }
+
+    fn block_hash(&self, height: u32) -> Result<Option<BlockHash>, ArtifactError> {
+        Ok(self.chain.get(height as usize).copied())
+    }
+
+    fn block(&self, hash: &BlockHash) -> Result<Block, ArtifactError> {
+        self.read(hash, None)
+    }
+
+    fn header(&self, hash: &BlockHash) -> Result<Header, ArtifactError> {
+        self.read(hash, Some(80))
+    }
+}
+
+/// Obfuscation key (all zeros, a no-op, before Bitcoin Core 28)
+fn read_xor_key(dir: &Path) -> Result<[u8; 8], ArtifactError> {
+    let path = dir.join("xor.dat");
+
+    match std::fs::read(&path) {
+        Ok(key) => key
+            .try_into()
+            .map_err(|_| ArtifactError::BlockFile(format!("{} must hold 8 bytes", path.display()))),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok([0; 8]),
+        Err(e) => Err(io_error(&path, e)),
+    }
+}
+
+fn block_file_path(dir: &Path, file: u32) -> PathBuf {
+    dir.join(format!("blk{:05}.dat", file))
+}
+
+/// Headers and locations of every block record in one file
+fn scan_file(path: &Path, file_number: u32, magic: &[u8; 4], xor_key: &[u8; 8]) -> Result<Vec<(Header, Location)>, ArtifactError> {
+    let mut file = File::open(path).map_err(|e| io_error(path, e))?;
+    let length = file.metadata().map_err(|e| io_error(path, e))?.len();
+    let mut records = vec![];
+    let mut offset = 0;
+
+    // Record: magic, little endian size, serialized block
+    while offset + 8 + 80 <= length {
+        let preamble = read_at(&mut file, offset, 8, xor_key).map_err(|e| io_error(path, e))?;
+
+        // Preallocated, never written
+        if preamble[..4] == [0; 4] {
+            break;
+        }
+        if preamble[..4] != magic[..] {
+            return Err(ArtifactError::BlockFile(format!(
+                "{} at {}: unexpected magic {:02x?}",
+                path.display(),
+                offset,
+                &preamble[..4]
+            )));
+        }
+
+        let size = u32::from_le_bytes(preamble[4..].try_into().unwrap());
+        // Still being written
+        if offset + 8 + u64::from(size) > length {
+            break;
+        }
+
+        let header_bytes = read_at(&mut file, offset + 8, 80, xor_key).map_err(|e| io_error(path, e))?;
+        let header: Header = deserialize(&header_bytes)
+            .map_err(|e| ArtifactError::BlockFile(format!("{} at {}: {}", path.display(), offset, e)))?;
+
+        records.push((
+            header,
+            Location {
+                file: file_number,
+                offset: offset + 8,
+                size,
+            },
+        ));
+        offset += 8 + u64::from(size);
+    }
+
+    Ok(records)
+}
+
+/// Read `len` bytes at `offset`, undoing the obfuscation
+fn read_at(file: &mut File, offset: u64, len: usize, xor_key: &[u8; 8]) -> std::io::Result<Vec<u8>> {
+    let mut bytes = vec![0; len];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut bytes)?;
+
+    // The key cycles over the file position, not the record
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte ^= xor_key[((offset + i as u64) % 8) as usize];
+    }
+
+    Ok(bytes)
+}
+
+/// Hashes from genesis to the most-work tip (ties go to the block stored
+/// first, like bitcoind)
+fn best_chain(headers: &[Header]) -> Vec<BlockHash> {
+    let by_hash = headers
+        .iter()
+        .map(|header| (header.block_hash(), header))
+        .collect::<HashMap<_, _>>();
+    // Chain work of every block connecting back to genesis
+    let mut work: HashMap<BlockHash, Work> = HashMap::new();
+    let mut best: Option<(BlockHash, Work)> = None;
+
+    for header in headers {
+        // Walk back to a block with known work (iteratively, chains are long)
+        let mut unresolved = vec![];
+        let mut hash = header.block_hash();
+        let mut base = loop {
+            if let Some(known) = work.get(&hash) {
+                break Some(*known);
+            }
+            match by_hash.get(&hash) {
+                Some(header) => {
+                    unresolved.push(*header);
+                    if header.prev_blockhash == BlockHash::all_zeros() {
+                        break None;
+                    }
+                    hash = header.prev_blockhash;
+                }
+                // Orphan: its ancestors were never stored
+                None => {
+                    unresolved.clear();
+                    break None;
+                }
+            }
+        };
+
+        for header in unresolved.into_iter().rev() {
+            let total = base.map_or(header.work(), |base| base + header.work());
+            work.insert(header.block_hash(), total);
+            base = Some(total);
+        }
+
+        if let Some(total) = work.get(&header.block_hash()) {
+            let better = match best {
+                Some((_, best_work)) => *total > best_work,
+                None => true,
+            };
+            if better {
+                best = Some((header.block_hash(), *total));
+            }
+        }
+    }
+
+    let mut chain = vec![];
+    let mut next = best.map(|(hash, _)| hash);
+    while let Some(hash) = next {
+        chain.push(hash);
+        next = by_hash
+            .get(&hash)
+            .map(|header| header.prev_blockhash)
+            .filter(|prev| *prev != BlockHash::all_zeros());
+    }
+    chain.reverse();
+
+    chain
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ArtifactError {
+    ArtifactError::BlockFile(format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use bitcoin::consensus::serialize;
+    use std::fs;
+
+    const XOR_KEY: [u8; 8] = [0x5a, 0x01, 0xff, 0x10, 0x77, 0x00, 0x3c, 0xa5];
+
+    /// Fresh directory under the system temp dir
+    fn blocks_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("artifact-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Write `blocks` the way bitcoind does: records, zero padding, XOR
+    fn write_block_file(dir: &Path, file: u32, blocks: &[&Block], xor_key: &[u8; 8]) {
+        let mut bytes = vec![];
+        for block in blocks {
+            let data = serialize(*block);
+            bytes.extend(Network::Regtest.magic().to_bytes());
+            bytes.extend((data.len() as u32).to_le_bytes());
+            bytes.extend(data);
+        }
+        bytes.extend([0; 64]);
+
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte ^= xor_key[i % 8];
+        }
+        fs::write(block_file_path(dir, file), bytes).unwrap();
+    }
+
+    fn tagged(prev: &Block, time: u32) -> Block {
+        let mut block = fixtures::block(prev.block_hash(), vec![]);
+        block.header.time = time;
+        block
+    }
+
+    #[test]
+    fn open_rebuilds_best_chain_in_height_order() {
+        let dir = blocks_dir("blkfiles");
+        let genesis = fixtures::block(BlockHash::all_zeros(), vec![]);
+        let a1 = tagged(&genesis, 1);
+        let a2 = tagged(&a1, 2);
+        let a3 = tagged(&a2, 3);
+        // Stale branch with less work
+        let b1 = tagged(&genesis, 100);
+        let b2 = tagged(&b1, 101);
+
+        // Out of order across two files
+        write_block_file(&dir, 0, &[&genesis, &b1, &a2, &a1], &XOR_KEY);
+        write_block_file(&dir, 1, &[&b2, &a3], &XOR_KEY);
+        fs::write(dir.join("xor.dat"), XOR_KEY).unwrap();
+
+        let reader = BlockFileReader::open(&dir, Network::Regtest).unwrap();
+
+        // Case: Most Work Tip
+        assert_eq!(reader.tip().unwrap().height, 3);
+        // Case: Height Order
+        for (height, block) in [&genesis, &a1, &a2, &a3].into_iter().enumerate() {
+            assert_eq!(reader.block_at(height as u32).unwrap().unwrap(), *block);
+        }
+        // Case: Past The Tip
+        assert!(reader.block_at(4).unwrap().is_none());
+        // Case: Stale Header
+        assert_eq!(reader.header(&b2.block_hash()).unwrap(), b2.header);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn truncate_to_keeps_the_node_chain() {
+        let dir = blocks_dir("blkfiles-node");
+        let genesis = fixtures::block(BlockHash::all_zeros(), vec![]);
+        let a1 = tagged(&genesis, 1);
+        let a2 = tagged(&a1, 2);
+        // Invalid branch with more blocks, which the node rejected
+        let b1 = tagged(&genesis, 100);
+        let b2 = tagged(&b1, 101);
+        let b3 = tagged(&b2, 102);
+
+        write_block_file(&dir, 0, &[&genesis, &a1, &a2, &b1, &b2, &b3], &[0; 8]);
+        let mut reader = BlockFileReader::open(&dir, Network::Regtest).unwrap();
+
+        // Case: Unvalidated Most Work Tip
+        assert_eq!(reader.tip().unwrap().hash, b3.block_hash());
+
+        reader.truncate_to(&fixtures::chain(vec![genesis.clone(), a1.clone(), a2.clone()])).unwrap();
+
+        // Case: Capped At The Fork
+        assert_eq!(reader.tip().unwrap().hash, genesis.block_hash());
+        assert!(reader.block_at(1).unwrap().is_none());
+
+        let mut reader = BlockFileReader::open(&dir, Network::Regtest).unwrap();
+        reader.truncate_to(&fixtures::chain(vec![genesis.clone(), b1.clone()])).unwrap();
+
+        // Case: Capped At The Node Tip
+        assert_eq!(reader.tip().unwrap().hash, b1.block_hash());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn open_requires_the_xor_key() {
+        let dir = blocks_dir("blkfiles-xor");
+        let genesis = fixtures::block(BlockHash::all_zeros(), vec![]);
+        write_block_file(&dir, 0, &[&genesis], &XOR_KEY);
+
+        // Case: Missing Key
+        let error = BlockFileReader::open(&dir, Network::Regtest).err().unwrap();
+        assert!(error.to_string().contains("unexpected magic"));
+
+        fs::write(dir.join("xor.dat"), XOR_KEY).unwrap();
+
+        // Case: Key Present
+        assert_eq!(BlockFileReader::open(&dir, Network::Regtest).unwrap().tip().unwrap().hash, genesis.block_hash());
+        // Case: Other Network
+        assert!(BlockFileReader::open(&dir, Network::Bitcoin).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
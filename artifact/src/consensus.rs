@@ -0,0 +1,162 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::ArtifactError;
+use crate::genesis::Genesis;
+use crate::models::block::fetch_ledger_hash;
+use crate::schema::{address_events, credits, debits, issuances, ownership_transfers};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use diesel::prelude::*;
+
+/// Ledger hash preceding the genesis state
+pub const INITIAL_LEDGER_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Ledger events written at `block_height`, in a backend independent
+/// canonical form (issuances, then ownership transfers, then debits, then
+/// credits, then address changes, each in the order they were applied)
+pub fn block_events(conn: &mut DbConnection, block_height: i32) -> Result<Vec<String>, ArtifactError> {
+    let mut events = vec![];
+
+    let issued = with_backend!(conn, |conn| {
+        issuances::table
+            .filter(issuances::block_height.eq(block_height))
+            .order(issuances::id.asc())
+            .select((
+                issuances::txid,
+                issuances::token,
+                issuances::issuer,
+                issuances::quantity,
+                issuances::divisibility,
+                issuances::flags,
+            ))
+            .load::<(String, String, String, i64, i32, i32)>(conn)
+    })?;
+    for (txid, token, issuer, quantity, divisibility, flags) in issued {
+        events.push(format!(
+            "issuance|{}|{}|{}|{}|{}|{}",
+            txid, token, issuer, quantity, divisibility, flags
+        ));
+    }
+
+    let transferred = with_backend!(conn, |conn| {
+        ownership_transfers::table
+            .filter(ownership_transfers::block_height.eq(block_height))
+            .order(ownership_transfers::id.asc())
+            .select((
+                ownership_transfers::txid,
+                ownership_transfers::token,
+                ownership_transfers::source,
+                ownership_transfers::destination,
+            ))
+            .load::<(String, String, String, String)>(conn)
+    })?;
+    for (txid, token, source, destination) in transferred {
+        events.push(format!("transfer|{}|{}|{}|{}", txid, token, source, destination));
+    }
+
+    let debited = with_backend!(conn, |conn| {
+        debits::table
+            .filter(debits::block_height.eq(block_height))
+            .order(debits::id.asc())
+            .select((debits::txid, debits::event, debits::address, debits::token, debits::quantity))
+            .load::<(String, String, String, String, i64)>(conn)
+    })?;
+    for (txid, event, address, token, quantity) in debited {
+        events.push(format!("debit|{}|{}|{}|{}|{}", txid, event, address, token, quantity));
+    }
+
+    let credited = with_backend!(conn, |conn| {
+        credits::table
+            .filter(credits::block_height.eq(block_height))
+            .order(credits::id.asc())
+            .select((
+                credits::txid,
+                credits::event,
+                credits::address,
+                credits::token,
+                credits::quantity,
+                credits::memo,
+            ))
+            .load::<(String, String, String, String, i64, Option<String>)>(conn)
+    })?;
+    for (txid, event, address, token, quantity, memo) in credited {
+        let mut event = format!("credit|{}|{}|{}|{}|{}", txid, event, address, token, quantity);
+        // Memos are the last field, so a '|' inside one stays unambiguous
+        if let Some(memo) = memo {
+            event.push('|');
+            event.push_str(&memo);
+        }
+        events.push(event);
+    }
+
+    let changed = with_backend!(conn, |conn| {
+        address_events::table
+            .filter(address_events::block_height.eq(block_height))
+            .order(address_events::id.asc())
+            .select((
+                address_events::txid,
+                address_events::event,
+                address_events::address,
+                address_events::flags,
+                address_events::unlock_height,
+            ))
+            .load::<(Option<String>, String, String, i32, Option<i32>)>(conn)
+    })?;
+    for (txid, event, address, flags, unlock_height) in changed {
+        // Releases have no transaction, cleared unlock heights no value
+        events.push(format!(
+            "address|{}|{}|{}|{}|{}",
+            txid.unwrap_or_default(),
+            event,
+            address,
+            flags,
+            unlock_height.map(|h| h.to_string()).unwrap_or_default()
+        ));
+    }
+
+    Ok(events)
+}
+
+/// Chain `events` onto the previous block's ledger hash
+pub fn ledger_hash(prev_ledger_hash: &str, events: &[String]) -> String {
+    let mut engine = sha256::Hash::engine();
+
+    engine.input(prev_ledger_hash.as_bytes());
+    for event in events {
+        engine.input(event.as_bytes());
+        engine.input(b"\n");
+    }
+
+    sha256::Hash::from_engine(engine).to_string()
+}
+
+/// Ledger hash for `block_height`, chained to the block below it (or to
+/// the genesis state for the first block)
+pub fn compute_ledger_hash(conn: &mut DbConnection, genesis: &Genesis, block_height: i32) -> Result<String, ArtifactError> {
+    let prev_ledger_hash = fetch_ledger_hash(conn, block_height - 1)?
+        .unwrap_or_else(|| genesis.ledger_hash());
+    let events = block_events(conn, block_height)?;
+
+    Ok(ledger_hash(&prev_ledger_hash, &events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ledger_hash_chains_previous_hash() {
+        let events = vec!["credit|txid|send|bob|ABC|1".to_string()];
+
+        // Case: Deterministic
+        assert_eq!(
+            ledger_hash(INITIAL_LEDGER_HASH, &events),
+            ledger_hash(INITIAL_LEDGER_HASH, &events)
+        );
+        // Case: Previous Hash Matters
+        assert_ne!(
+            ledger_hash(INITIAL_LEDGER_HASH, &events),
+            ledger_hash(&ledger_hash(INITIAL_LEDGER_HASH, &[]), &events)
+        );
+        // Case: Events Matter
+        assert_ne!(ledger_hash(INITIAL_LEDGER_HASH, &events), ledger_hash(INITIAL_LEDGER_HASH, &[]));
+    }
+}
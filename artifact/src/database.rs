@@ -0,0 +1,104 @@
+use crate::error::ArtifactError;
+use diesel::connection::{AnsiTransactionManager, TransactionManager};
+use diesel::prelude::*;
+
+/// Database Connection
+///
+/// The backend is picked from the connection URL: `postgres://` and
+/// `postgresql://` URLs open PostgreSQL (with the `postgres` feature),
+/// anything else is a SQLite path.
+pub enum DbConnection {
+    Sqlite(SqliteConnection),
+    #[cfg(feature = "postgres")]
+    Postgres(PgConnection),
+}
+
+/// Run the same diesel query against whichever backend `$conn` holds
+macro_rules! with_backend {
+    ($conn:expr, |$inner:ident| $body:expr) => {
+        match $conn {
+            $crate::database::DbConnection::Sqlite($inner) => $body,
+            #[cfg(feature = "postgres")]
+            $crate::database::DbConnection::Postgres($inner) => $body,
+        }
+    };
+}
+
+pub(crate) use with_backend;
+
+impl DbConnection {
+    pub fn establish(database_url: &str) -> Result<Self, ArtifactError> {
+        if is_postgres_url(database_url) {
+            #[cfg(feature = "postgres")]
+            return Ok(DbConnection::Postgres(PgConnection::establish(database_url)?));
+
+            #[cfg(not(feature = "postgres"))]
+            return Err(ArtifactError::Configuration(
+                "DATABASE_URL is PostgreSQL but the postgres feature is disabled".to_string(),
+            ));
+        }
+
+        Ok(DbConnection::Sqlite(SqliteConnection::establish(database_url)?))
+    }
+
+    /// Backend name (for logs)
+    pub fn backend(&self) -> &'static str {
+        match self {
+            DbConnection::Sqlite(_) => "sqlite",
+            #[cfg(feature = "postgres")]
+            DbConnection::Postgres(_) => "postgres",
+        }
+    }
+
+    /// Run `f` atomically, a nested call is a savepoint
+    pub fn transaction<T, F>(&mut self, f: F) -> Result<T, ArtifactError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, ArtifactError>,
+    {
+        with_backend!(self, |conn| AnsiTransactionManager::begin_transaction(conn))?;
+
+        match f(self) {
+            Ok(value) => {
+                with_backend!(self, |conn| AnsiTransactionManager::commit_transaction(conn))?;
+                Ok(value)
+            }
+            Err(e) => match with_backend!(self, |conn| AnsiTransactionManager::rollback_transaction(conn)) {
+                // The original error explains a broken transaction better
+                Ok(()) | Err(diesel::result::Error::BrokenTransactionManager) => Err(e),
+                Err(rollback_error) => Err(rollback_error.into()),
+            },
+        }
+    }
+}
+
+impl From<SqliteConnection> for DbConnection {
+    fn from(conn: SqliteConnection) -> Self {
+        DbConnection::Sqlite(conn)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<PgConnection> for DbConnection {
+    fn from(conn: PgConnection) -> Self {
+        DbConnection::Postgres(conn)
+    }
+}
+
+fn is_postgres_url(database_url: &str) -> bool {
+    database_url.starts_with("postgres://") || database_url.starts_with("postgresql://")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn establish_picks_backend_from_url() {
+        // Case: SQLite Path
+        assert_eq!(DbConnection::establish(":memory:").unwrap().backend(), "sqlite");
+        // Case: PostgreSQL URL
+        assert!(is_postgres_url("postgres://artifact@localhost/artifact"));
+        assert!(is_postgres_url("postgresql://localhost"));
+        assert!(!is_postgres_url("artifact.db"));
+    }
+}
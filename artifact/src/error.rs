@@ -0,0 +1,270 @@
+use crate::protocol::DecodeError;
+use std::fmt;
+use thiserror::Error;
+
+/// Library Errors
+///
+/// `code()` is stable across releases: services may match on it, log it
+/// or return it to users. New variants and reasons get new codes, existing
+/// codes are never reused.
+#[derive(Debug, Error)]
+pub enum ArtifactError {
+    #[error("Database: {0}")]
+    Database(#[from] diesel::result::Error),
+    #[error("Connection: {0}")]
+    Connection(#[from] diesel::ConnectionError),
+    #[error("Configuration: {0}")]
+    Configuration(String),
+    /// The database was built by an incompatible version
+    #[error("Schema: {0}")]
+    Schema(String),
+    /// A store was asked to change something it does not hold
+    #[error("NotFound: {0}")]
+    NotFound(String),
+    /// A store was asked to create something it already holds
+    #[error("AlreadyExists: {0}")]
+    AlreadyExists(String),
+    #[error("Rpc: {0}")]
+    Rpc(#[from] bitcoincore_rpc::Error),
+    /// Reading bitcoind's `blk*.dat` files failed
+    #[error("BlockFile: {0}")]
+    BlockFile(String),
+    /// Bitcoin Core's REST interface failed or answered garbage
+    #[error("Rest: {0}")]
+    Rest(String),
+    /// A value breaks a format or range rule
+    #[error("{reason}: {detail}")]
+    Validation { reason: Reason, detail: String },
+    /// A well-formed message conflicts with the ledger
+    #[error("{reason}: {detail}")]
+    Rejected { reason: Reason, detail: String },
+    /// An envelope that does not decode to a message
+    #[error("{0}")]
+    Decode(#[from] DecodeError),
+}
+
+impl ArtifactError {
+    pub fn validation(reason: Reason, detail: impl Into<String>) -> Self {
+        ArtifactError::Validation {
+            reason,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn rejected(reason: Reason, detail: impl Into<String>) -> Self {
+        ArtifactError::Rejected {
+            reason,
+            detail: detail.into(),
+        }
+    }
+
+    /// Stable Code
+    pub fn code(&self) -> u16 {
+        match self {
+            ArtifactError::Database(_) => 100,
+            ArtifactError::Connection(_) => 101,
+            ArtifactError::Configuration(_) => 102,
+            ArtifactError::Schema(_) => 103,
+            ArtifactError::NotFound(_) => 104,
+            ArtifactError::AlreadyExists(_) => 105,
+            ArtifactError::Rpc(_) => 200,
+            ArtifactError::BlockFile(_) => 201,
+            ArtifactError::Rest(_) => 202,
+            ArtifactError::Decode(e) => match e {
+                DecodeError::EmptyMessage => 300,
+                DecodeError::UnknownMessageType(_) => 301,
+                DecodeError::InvalidPayloadLength { .. } => 302,
+                DecodeError::InvalidFlags(_) => 303,
+                DecodeError::InvalidDivisibility(_) => 304,
+                DecodeError::InvalidMemo(_) => 305,
+            },
+            ArtifactError::Validation { reason, .. } | ArtifactError::Rejected { reason, .. } => reason.code(),
+        }
+    }
+
+    /// Reason the message was invalid, if the error is about the message
+    pub fn reason(&self) -> Option<Reason> {
+        match self {
+            ArtifactError::Validation { reason, .. } | ArtifactError::Rejected { reason, .. } => Some(*reason),
+            _ => None,
+        }
+    }
+
+    /// The message was invalid (skip it), rather than the node failing
+    pub fn is_rejection(&self) -> bool {
+        matches!(
+            self,
+            ArtifactError::Validation { .. } | ArtifactError::Rejected { .. } | ArtifactError::Decode(_)
+        )
+    }
+}
+
+/// Validation and Rejection Reasons
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    InvalidTokenId,
+    InvalidTokenLength,
+    InvalidTokenCharacters,
+    InvalidTokenLetterCase,
+    InvalidQuantity,
+    InvalidDivisibility,
+    InvalidFlags,
+    InvalidAddress,
+    MissingSource,
+    MissingDestination,
+    MissingMemo,
+    UnknownToken,
+    InvalidOwner,
+    InvalidNamespace,
+    TokenLocked,
+    InsufficientBalance,
+    AddressLocked,
+    AddressNotLocked,
+    UnlockPending,
+    ReservedToken,
+    NotActivated,
+}
+
+impl Reason {
+    pub const ALL: [Reason; 21] = [
+        Reason::InvalidTokenId,
+        Reason::InvalidTokenLength,
+        Reason::InvalidTokenCharacters,
+        Reason::InvalidTokenLetterCase,
+        Reason::InvalidQuantity,
+        Reason::InvalidDivisibility,
+        Reason::InvalidFlags,
+        Reason::InvalidAddress,
+        Reason::MissingSource,
+        Reason::MissingDestination,
+        Reason::MissingMemo,
+        Reason::UnknownToken,
+        Reason::InvalidOwner,
+        Reason::InvalidNamespace,
+        Reason::TokenLocked,
+        Reason::InsufficientBalance,
+        Reason::AddressLocked,
+        Reason::AddressNotLocked,
+        Reason::UnlockPending,
+        Reason::ReservedToken,
+        Reason::NotActivated,
+    ];
+
+    /// Stable Code
+    pub fn code(&self) -> u16 {
+        match self {
+            Reason::InvalidTokenId => 400,
+            Reason::InvalidTokenLength => 401,
+            Reason::InvalidTokenCharacters => 402,
+            Reason::InvalidTokenLetterCase => 403,
+            Reason::InvalidQuantity => 404,
+            Reason::InvalidDivisibility => 405,
+            Reason::InvalidFlags => 406,
+            Reason::InvalidAddress => 407,
+            Reason::MissingSource => 408,
+            Reason::MissingDestination => 409,
+            Reason::MissingMemo => 410,
+            Reason::UnknownToken => 411,
+            Reason::InvalidOwner => 412,
+            Reason::InvalidNamespace => 413,
+            Reason::TokenLocked => 414,
+            Reason::InsufficientBalance => 415,
+            Reason::AddressLocked => 416,
+            Reason::AddressNotLocked => 417,
+            Reason::UnlockPending => 418,
+            Reason::ReservedToken => 419,
+            Reason::NotActivated => 420,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Reason::InvalidTokenId => "InvalidTokenId",
+            Reason::InvalidTokenLength => "InvalidTokenLength",
+            Reason::InvalidTokenCharacters => "InvalidTokenCharacters",
+            Reason::InvalidTokenLetterCase => "InvalidTokenLetterCase",
+            Reason::InvalidQuantity => "InvalidQuantity",
+            Reason::InvalidDivisibility => "InvalidDivisibility",
+            Reason::InvalidFlags => "InvalidFlags",
+            Reason::InvalidAddress => "InvalidAddress",
+            Reason::MissingSource => "MissingSource",
+            Reason::MissingDestination => "MissingDestination",
+            Reason::MissingMemo => "MissingMemo",
+            Reason::UnknownToken => "UnknownToken",
+            Reason::InvalidOwner => "InvalidOwner",
+            Reason::InvalidNamespace => "InvalidNamespace",
+            Reason::TokenLocked => "TokenLocked",
+            Reason::InsufficientBalance => "InsufficientBalance",
+            Reason::AddressLocked => "AddressLocked",
+            Reason::AddressNotLocked => "AddressNotLocked",
+            Reason::UnlockPending => "UnlockPending",
+            Reason::ReservedToken => "ReservedToken",
+            Reason::NotActivated => "NotActivated",
+        }
+    }
+
+    pub fn from_code(code: u16) -> Option<Self> {
+        Reason::ALL.into_iter().find(|reason| reason.code() == code)
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_unique_and_round_trip() {
+        let decode_errors = [
+            DecodeError::EmptyMessage,
+            DecodeError::UnknownMessageType(0),
+            DecodeError::InvalidPayloadLength { expected: 0, actual: 0 },
+            DecodeError::InvalidFlags(0),
+            DecodeError::InvalidDivisibility(0),
+            DecodeError::InvalidMemo(String::new()),
+        ];
+        let mut errors = vec![
+            ArtifactError::Database(diesel::result::Error::NotFound),
+            ArtifactError::Connection(diesel::ConnectionError::BadConnection(String::new())),
+            ArtifactError::Configuration(String::new()),
+            ArtifactError::Schema(String::new()),
+            ArtifactError::NotFound(String::new()),
+            ArtifactError::AlreadyExists(String::new()),
+            ArtifactError::Rpc(bitcoincore_rpc::Error::ReturnedError(String::new())),
+            ArtifactError::BlockFile(String::new()),
+            ArtifactError::Rest(String::new()),
+        ];
+        errors.extend(decode_errors.map(ArtifactError::Decode));
+        errors.extend(Reason::ALL.map(|reason| ArtifactError::rejected(reason, "")));
+
+        let mut codes = errors.iter().map(|error| error.code()).collect::<Vec<_>>();
+        codes.sort_unstable();
+        codes.dedup();
+
+        // Case: Unique
+        assert_eq!(codes.len(), errors.len());
+        // Case: Decode Codes
+        assert!((300..=305).all(|code| codes.contains(&code)));
+        // Case: Round Trip
+        for reason in Reason::ALL {
+            assert_eq!(Reason::from_code(reason.code()), Some(reason));
+        }
+    }
+
+    #[test]
+    fn display_keeps_reason_prefix() {
+        let error = ArtifactError::rejected(Reason::InvalidOwner, "ABC is not owned by bob");
+
+        // Case: Reason Then Detail
+        assert_eq!(error.to_string(), "InvalidOwner: ABC is not owned by bob");
+        assert_eq!(error.code(), 412);
+        // Case: Rejection Vs Failure
+        assert!(error.is_rejection());
+        assert!(!ArtifactError::Database(diesel::result::Error::NotFound).is_rejection());
+    }
+}
@@ -0,0 +1,123 @@
+use bitcoin::absolute::LockTime;
+use bitcoin::block::{Header, Version as BlockVersion};
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1::{Secp256k1, SecretKey};
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, Block, BlockHash, CompactTarget, Network, OutPoint, PublicKey, ScriptBuf,
+    Sequence, Transaction, TxIn, TxMerkleNode, TxOut, Txid, Witness,
+};
+use crate::database::DbConnection;
+use crate::migrations;
+
+pub fn establish_test_connection() -> DbConnection {
+    let mut conn = DbConnection::establish(":memory:")
+        .expect("Failed to create an in-memory database");
+
+    // Run pending migrations
+    migrations::prepare(&mut conn, true)
+        .expect("Failed to run migrations");
+
+    conn
+}
+
+/// PostgreSQL database from `POSTGRES_TEST_URL`, inside a transaction that
+/// is never committed (`None` when the variable is unset)
+#[cfg(feature = "postgres")]
+pub fn establish_test_postgres_connection() -> Option<DbConnection> {
+    use diesel::Connection;
+
+    let database_url = std::env::var("POSTGRES_TEST_URL").ok()?;
+    let mut conn = diesel::PgConnection::establish(&database_url)
+        .expect("Failed to connect to the test database");
+
+    conn.begin_test_transaction()
+        .expect("Failed to begin a test transaction");
+
+    let mut conn = DbConnection::Postgres(conn);
+    migrations::prepare(&mut conn, true)
+        .expect("Failed to run migrations");
+
+    Some(conn)
+}
+
+/// Deterministic key for test wallet `n`
+pub fn public_key(n: u8) -> PublicKey {
+    let secret = SecretKey::from_slice(&[n.max(1); 32]).unwrap();
+    PublicKey::new(secret.public_key(&Secp256k1::new()))
+}
+
+/// P2WPKH address for test wallet `n`
+pub fn address(n: u8) -> Address {
+    Address::p2wpkh(&public_key(n), Network::Bitcoin).unwrap()
+}
+
+/// Transaction spending from wallet `n` with the given outputs
+pub fn transaction(n: u8, outputs: Vec<ScriptBuf>) -> Transaction {
+    let mut witness = Witness::new();
+    witness.push([0u8; 72]);
+    witness.push(public_key(n).to_bytes());
+
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::from_byte_array([n; 32]), 0),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness,
+        }],
+        output: outputs
+            .into_iter()
+            .map(|script_pubkey| TxOut {
+                value: Amount::ZERO,
+                script_pubkey,
+            })
+            .collect(),
+    }
+}
+
+/// Block on top of `prev_blockhash` with the given transactions
+pub fn block(prev_blockhash: BlockHash, txdata: Vec<Transaction>) -> Block {
+    let mut block = Block {
+        header: Header {
+            version: BlockVersion::ONE,
+            prev_blockhash,
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 0,
+            bits: CompactTarget::from_consensus(0x207fffff),
+            nonce: 0,
+        },
+        txdata,
+    };
+
+    // Distinct contents, distinct hashes
+    if let Some(merkle_root) = block.compute_merkle_root() {
+        block.header.merkle_root = merkle_root;
+    }
+
+    block
+}
+
+/// Outputs spent by `block`, following `transaction`'s convention that
+/// wallet `n` spends output 0 of txid `[n; 32]`, paid to its address
+pub fn spent_scripts(block: &Block) -> std::collections::HashMap<OutPoint, ScriptBuf> {
+    block
+        .txdata
+        .iter()
+        .flat_map(|tx| &tx.input)
+        .map(|input| {
+            let n = input.previous_output.txid.to_byte_array()[0];
+            (input.previous_output, address(n).script_pubkey())
+        })
+        .collect()
+}
+
+/// Chain of `blocks` where the test wallets (below 255) are funded
+pub fn chain(blocks: Vec<Block>) -> crate::source::MemoryChain {
+    let chain = crate::source::MemoryChain::new(blocks);
+    for n in 0..u8::MAX {
+        chain.fund(OutPoint::new(Txid::from_byte_array([n; 32]), 0), address(n).script_pubkey());
+    }
+    chain
+}
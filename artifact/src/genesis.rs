@@ -0,0 +1,411 @@
+use crate::consensus::{ledger_hash, INITIAL_LEDGER_HASH};
+use crate::error::ArtifactError;
+use crate::models::address::validate_address;
+use crate::models::message::{Event, Origin};
+use crate::models::token::{check_token, Flags, NewToken};
+use crate::protocol::MessageType;
+use crate::store::LedgerStore;
+use bitcoin::Network;
+use std::collections::HashMap;
+
+/// Transaction id recorded for genesis allocations, which have none
+pub const GENESIS_TXID: &str = "genesis";
+
+/// Names nobody can issue (Bitcoin's own units)
+pub const RESERVED: &[&str] = &["XBT", "SAT", "SATS"];
+
+/// Token existing before the first block
+pub struct GenesisToken {
+    pub token: &'static str,
+    pub flags: i32,
+    pub divisibility: i32,
+    pub owner: &'static str,
+}
+
+/// Balance existing before the first block
+pub struct Allocation {
+    pub address: &'static str,
+    pub token: &'static str,
+    pub quantity: i64,
+}
+
+/// Height from which a message type is accepted
+pub struct Activation {
+    pub message_type: MessageType,
+    pub height: i32,
+}
+
+/// Network Genesis
+///
+/// Ledger state applied before `start_height`, the first block the
+/// indexer reads. Reserved names become locked tokens without an owner,
+/// so they can never be issued. The genesis state is hashed into the
+/// ledger hash chain in place of the first block's predecessor, and its
+/// allocations are journaled as credits of `start_height`. Message types
+/// without an activation are accepted from `start_height`.
+pub struct Genesis {
+    pub network: Network,
+    /// No start height chosen yet, so the indexer refuses the spec
+    pub placeholder: bool,
+    pub start_height: i32,
+    pub activations: &'static [Activation],
+    pub reserved: &'static [&'static str],
+    pub tokens: &'static [GenesisToken],
+    pub allocations: &'static [Allocation],
+}
+
+pub const MAINNET: Genesis = Genesis {
+    network: Network::Bitcoin,
+    placeholder: true,
+    start_height: 0,
+    activations: &[],
+    reserved: RESERVED,
+    tokens: &[],
+    allocations: &[],
+};
+
+pub const TESTNET: Genesis = Genesis {
+    network: Network::Testnet,
+    placeholder: true,
+    start_height: 0,
+    activations: &[],
+    reserved: RESERVED,
+    tokens: &[],
+    allocations: &[],
+};
+
+pub const SIGNET: Genesis = Genesis {
+    network: Network::Signet,
+    placeholder: true,
+    start_height: 0,
+    activations: &[],
+    reserved: RESERVED,
+    tokens: &[],
+    allocations: &[],
+};
+
+pub const REGTEST: Genesis = Genesis {
+    network: Network::Regtest,
+    placeholder: false,
+    start_height: 0,
+    activations: &[],
+    reserved: RESERVED,
+    tokens: &[],
+    allocations: &[],
+};
+
+impl Genesis {
+    /// The spec to index `network` with (placeholders are refused)
+    pub fn for_network(network: Network) -> Result<&'static Genesis, ArtifactError> {
+        let genesis = match network {
+            Network::Bitcoin => &MAINNET,
+            Network::Testnet => &TESTNET,
+            Network::Signet => &SIGNET,
+            Network::Regtest => &REGTEST,
+            network => return Err(ArtifactError::Configuration(format!("No genesis for network {}", network))),
+        };
+
+        if genesis.placeholder {
+            return Err(ArtifactError::Configuration(format!(
+                "Genesis for {} is a placeholder until its start height is set",
+                network
+            )));
+        }
+
+        Ok(genesis)
+    }
+
+    /// First height accepting `message_type`
+    pub fn activation_height(&self, message_type: MessageType) -> i32 {
+        self.activations
+            .iter()
+            .find(|activation| activation.message_type == message_type)
+            .map_or(self.start_height, |activation| activation.height.max(self.start_height))
+    }
+
+    /// Validation (names, addresses and supplies)
+    pub fn validate(&self) -> Result<(), ArtifactError> {
+        let invalid = |detail: String| ArtifactError::Configuration(format!("Genesis {}: {}", self.network, detail));
+
+        let mut names = self.reserved.to_vec();
+        names.extend(self.tokens.iter().map(|t| t.token));
+        for (i, name) in names.iter().enumerate() {
+            check_token(name).map_err(|e| invalid(format!("{} {}", name, e)))?;
+            if names[..i].contains(name) {
+                return Err(invalid(format!("{} is declared twice", name)));
+            }
+        }
+
+        for token in self.tokens {
+            validate_address(token.owner, self.network).map_err(|e| invalid(format!("{} owner {}", token.token, e)))?;
+            if Flags::from_bits(token.flags).is_none() || !(0..=8).contains(&token.divisibility) {
+                return Err(invalid(format!("{} has invalid flags or divisibility", token.token)));
+            }
+        }
+
+        for allocation in self.allocations {
+            validate_address(allocation.address, self.network).map_err(|e| invalid(e.to_string()))?;
+            if !self.tokens.iter().any(|t| t.token == allocation.token) {
+                return Err(invalid(format!("{} is not a genesis token", allocation.token)));
+            }
+            if allocation.quantity <= 0 {
+                return Err(invalid(format!("{} allocation must be positive", allocation.token)));
+            }
+        }
+        self.supplies()?;
+
+        Ok(())
+    }
+
+    /// Genesis events, in the canonical form of `consensus::block_events`
+    pub fn events(&self) -> Vec<String> {
+        let mut events = vec![format!("genesis|{}|{}", self.network, self.start_height)];
+
+        for activation in self.activations {
+            events.push(format!("activate|{}|{}", activation.message_type.id(), activation.height));
+        }
+        for name in self.reserved {
+            events.push(format!("reserve|{}", name));
+        }
+        for token in self.tokens {
+            events.push(format!(
+                "token|{}|{}|{}|{}",
+                token.token, token.owner, token.divisibility, token.flags
+            ));
+        }
+        for allocation in self.allocations {
+            events.push(format!(
+                "allocation|{}|{}|{}",
+                allocation.address, allocation.token, allocation.quantity
+            ));
+        }
+
+        events
+    }
+
+    /// Ledger hash the first block chains onto
+    pub fn ledger_hash(&self) -> String {
+        ledger_hash(INITIAL_LEDGER_HASH, &self.events())
+    }
+
+    /// Write the genesis state into an empty ledger
+    pub fn apply<S: LedgerStore>(&self, store: &mut S) -> Result<(), ArtifactError> {
+        self.validate()?;
+        let supplies = self.supplies()?;
+
+        store.transaction(|store| {
+            let reserved_flags = (Flags::LOCKED | Flags::NAMESPACE).bits();
+            for name in self.reserved {
+                store.create_token(&NewToken {
+                    token: name,
+                    flags: &reserved_flags,
+                    divisibility: &0,
+                    owner: None,
+                    issuer: None,
+                    issued_at_height: None,
+                    issuance_txid: None,
+                    total_supply: &0,
+                    parent: None,
+                })?;
+            }
+
+            for token in self.tokens {
+                store.create_token(&NewToken {
+                    token: token.token,
+                    flags: &token.flags,
+                    divisibility: &token.divisibility,
+                    owner: Some(token.owner),
+                    issuer: Some(token.owner),
+                    issued_at_height: None,
+                    issuance_txid: None,
+                    total_supply: supplies.get(token.token).unwrap_or(&0),
+                    parent: None,
+                })?;
+            }
+
+            // Journaled like any credit, so balances rebuild and audit from it
+            let origin = Origin {
+                block_height: self.start_height,
+                tx_index: 0,
+                txid: GENESIS_TXID,
+                message_id: None,
+            };
+            for allocation in self.allocations {
+                let balance = store.fetch_quantity(allocation.address, allocation.token)? + allocation.quantity;
+
+                store.ensure_address(allocation.address)?;
+                store.create_credit(&origin, Event::Genesis, allocation.address, allocation.token, &allocation.quantity, None)?;
+                store.upsert_balance(allocation.address, allocation.token, &balance)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Total supply of each genesis token
+    fn supplies(&self) -> Result<HashMap<&'static str, i64>, ArtifactError> {
+        let mut supplies = HashMap::new();
+
+        for allocation in self.allocations {
+            let supply = supplies.entry(allocation.token).or_insert(0i64);
+            *supply = supply.checked_add(allocation.quantity).ok_or_else(|| {
+                ArtifactError::Configuration(format!("Genesis {} supply exceeds maximum", allocation.token))
+            })?;
+        }
+
+        Ok(supplies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Reason;
+    use crate::fixtures::establish_test_connection;
+    use crate::models::balance::{audit_balances, fetch_quantity, rebuild_balances};
+    use crate::models::credit::fetch_credits;
+    use crate::models::token;
+    use crate::store::MemoryStore;
+
+    const ORIGIN: Origin = Origin {
+        block_height: 1,
+        tx_index: 0,
+        txid: "txid",
+        message_id: Some(1),
+    };
+
+    const ALICE: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+    const BOB: &str = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2";
+
+    const FUNDED: Genesis = Genesis {
+        network: Network::Bitcoin,
+        placeholder: false,
+        start_height: 10,
+        activations: &[Activation {
+            message_type: MessageType::AddressOptions,
+            height: 20,
+        }],
+        reserved: &["RES"],
+        tokens: &[GenesisToken {
+            token: "GEN",
+            flags: 0,
+            divisibility: 2,
+            owner: ALICE,
+        }],
+        allocations: &[
+            Allocation {
+                address: ALICE,
+                token: "GEN",
+                quantity: 700,
+            },
+            Allocation {
+                address: BOB,
+                token: "GEN",
+                quantity: 300,
+            },
+        ],
+    };
+
+    #[test]
+    fn builtin_genesis_is_valid() {
+        let specs = [&MAINNET, &TESTNET, &SIGNET, &REGTEST];
+        let networks = [Network::Bitcoin, Network::Testnet, Network::Signet, Network::Regtest];
+        for (genesis, network) in specs.into_iter().zip(networks) {
+            // Case: Valid Spec
+            genesis.validate().unwrap();
+            assert_eq!(genesis.network, network);
+        }
+
+        // Case: Placeholders Refused
+        assert_eq!(Genesis::for_network(Network::Bitcoin).err().unwrap().code(), 102);
+        assert_eq!(Genesis::for_network(Network::Testnet).err().unwrap().code(), 102);
+        assert_eq!(Genesis::for_network(Network::Regtest).unwrap().network, Network::Regtest);
+        // Case: Active From Start
+        assert_eq!(MAINNET.activation_height(MessageType::Send), MAINNET.start_height);
+        // Case: Hash Differs Per Network
+        for (i, genesis) in specs.iter().enumerate() {
+            for other in &specs[..i] {
+                assert_ne!(genesis.ledger_hash(), other.ledger_hash());
+            }
+        }
+        assert_ne!(MAINNET.ledger_hash(), INITIAL_LEDGER_HASH);
+    }
+
+    #[test]
+    fn validate_rejects_bad_specs() {
+        let short_name = Genesis {
+            reserved: &["A"],
+            ..MAINNET
+        };
+        let unknown_token = Genesis {
+            allocations: &[Allocation {
+                address: ALICE,
+                token: "NOPE",
+                quantity: 1,
+            }],
+            ..MAINNET
+        };
+        let wrong_network = Genesis {
+            network: Network::Regtest,
+            ..FUNDED
+        };
+
+        // Case: Invalid Name
+        assert!(short_name.validate().is_err());
+        // Case: Allocation Of Unknown Token
+        assert!(unknown_token.validate().is_err());
+        // Case: Address From Another Network
+        FUNDED.validate().unwrap();
+        assert!(wrong_network.validate().is_err());
+    }
+
+    #[test]
+    fn apply_creates_tokens_and_allocations() {
+        let mut store = MemoryStore::new();
+
+        FUNDED.apply(&mut store).unwrap();
+
+        // Case: Activation Heights
+        assert_eq!(FUNDED.activation_height(MessageType::Send), 10);
+        assert_eq!(FUNDED.activation_height(MessageType::AddressOptions), 20);
+        // Case: Allocated
+        assert_eq!(store.fetch_quantity(ALICE, "GEN").unwrap(), 700);
+        assert_eq!(store.fetch_quantity(BOB, "GEN").unwrap(), 300);
+        // Case: Supply And Owner
+        assert_eq!(store.tokens["GEN"].total_supply, 1_000);
+        assert_eq!(store.tokens["GEN"].owner.as_deref(), Some(ALICE));
+        // Case: Owner Can Issue More
+        token::parse(&mut store, &ORIGIN, ALICE, token::generate_id("GEN").unwrap(), 5, 2, 0).unwrap();
+        assert_eq!(store.tokens["GEN"].total_supply, 1_005);
+        // Case: Reserved Name
+        assert_eq!(
+            token::parse(&mut store, &ORIGIN, ALICE, token::generate_id("RES").unwrap(), 5, 0, 0).unwrap_err().reason(),
+            Some(Reason::ReservedToken)
+        );
+        assert_eq!(
+            token::parse(&mut store, &ORIGIN, ALICE, token::generate_id("RES.SUB").unwrap(), 5, 0, 0).unwrap_err().reason(),
+            Some(Reason::ReservedToken)
+        );
+    }
+
+    #[test]
+    fn apply_journals_allocations() {
+        let mut conn = establish_test_connection();
+
+        FUNDED.apply(&mut conn).unwrap();
+        let credits = fetch_credits(&mut conn, ALICE).unwrap();
+
+        // Case: Genesis Provenance
+        assert_eq!(credits.len(), 1);
+        assert_eq!(credits[0].event, "genesis");
+        assert_eq!(credits[0].txid, GENESIS_TXID);
+        assert_eq!(credits[0].block_height, FUNDED.start_height);
+        assert_eq!(credits[0].message_id, None);
+        // Case: Audit Agrees
+        assert!(audit_balances(&mut conn).unwrap().is_empty());
+        // Case: Rebuild Keeps Allocations
+        rebuild_balances(&mut conn).unwrap();
+        assert_eq!(fetch_quantity(&mut conn, ALICE, "GEN").unwrap(), 700);
+        assert_eq!(fetch_quantity(&mut conn, BOB, "GEN").unwrap(), 300);
+    }
+}
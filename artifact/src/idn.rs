@@ -0,0 +1,93 @@
+/// Translation (RFC 3492)
+///
+/// Tokens may carry an internationalized label, `XN--` followed by
+/// punycode, the way domain names do. Only labels that decode are valid
+/// names. `input` is the label without its `XN--` prefix.
+pub fn decode_punycode(input: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+
+    fn adapt(mut delta: u32, points: u32, first: bool) -> u32 {
+        delta /= if first { 700 } else { 2 };
+        delta += delta / points;
+
+        let mut k = 0;
+        while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+            delta /= BASE - T_MIN;
+            k += BASE;
+        }
+
+        k + (BASE - T_MIN + 1) * delta / (delta + 38)
+    }
+
+    // Basic code points precede the last delimiter
+    let (basic, extended) = match input.rfind('-') {
+        Some(i) => (&input[..i], &input[i + 1..]),
+        None => ("", input),
+    };
+
+    let mut output: Vec<char> = basic.chars().collect();
+    let (mut n, mut i, mut bias) = (128u32, 0u32, 72u32);
+    let mut digits = extended.chars().peekable();
+
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w = 1u32;
+        let mut k = BASE;
+
+        loop {
+            let digit = match digits.next()? {
+                c @ 'A'..='Z' => c as u32 - 'A' as u32,
+                c @ 'a'..='z' => c as u32 - 'a' as u32,
+                c @ '0'..='9' => c as u32 - '0' as u32 + 26,
+                _ => return None,
+            };
+
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = if k <= bias {
+                T_MIN
+            } else if k >= bias + T_MAX {
+                T_MAX
+            } else {
+                k - bias
+            };
+
+            if digit < t {
+                break;
+            }
+
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+
+        let points = output.len() as u32 + 1;
+        bias = adapt(i - old_i, points, old_i == 0);
+        n = n.checked_add(i / points)?;
+        i %= points;
+
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+
+    Some(output.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_punycode_follows_rfc_3492() {
+        // Case: RFC 3492 Sample (Chinese)
+        assert_eq!(decode_punycode("ihqwcrb4cv8a8dqg056pqjye").as_deref(), Some("他们为什么不说中文"));
+        // Case: Basic And Extended Code Points
+        assert_eq!(decode_punycode("bcher-kva").as_deref(), Some("bücher"));
+        // Case: Uppercase Token Label
+        assert_eq!(decode_punycode("CQV902D").as_deref(), Some("艺术"));
+        // Case: Truncated
+        assert!(decode_punycode("1234567").is_none());
+        // Case: Invalid Digit
+        assert!(decode_punycode("cqv9_2d").is_none());
+    }
+}
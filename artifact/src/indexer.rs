@@ -1,6 +1,27 @@
-use crate::options::BitcoinRpcOptions;
-use bitcoincore_rpc::{Client, RpcApi};
-use bitcoin::Block;
+use crate::blockfile::BlockFileReader;
+use crate::consensus::compute_ledger_hash;
+use crate::database::{with_backend, DbConnection};
+use crate::error::{ArtifactError, Reason};
+use crate::establish_connection;
+use crate::genesis::Genesis;
+use crate::models::block::{create_block, delete_blocks_after, fetch_block, fetch_last_block};
+use crate::models::message::{create_message, NewMessage, Origin};
+use crate::models::ledger_info::check_network;
+use crate::models::rejection::{create_rejection, delete_rejections, NewRejection};
+use crate::models::address::{self, release_unlocks, reset_address_flags, Flags};
+use crate::models::balance::{fetch_quantity, upsert_balance};
+use crate::models::{ownership, send, token};
+use crate::notify::Notifier;
+use crate::options::{BitcoinRpcOptions, BlockApi, NotifyOptions, PrefetchOptions};
+use crate::prefetch::Prefetcher;
+use crate::protocol::{self, Message};
+use crate::source::{self, BlockSource};
+use crate::schema::{address_events, balances, credits, debits, issuances, messages, ownership_transfers, tokens};
+use bitcoin::{Block, OutPoint, ScriptBuf};
+use diesel::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
@@ -9,68 +30,1169 @@ pub struct BlockData {
 }
 
 pub struct Indexer {
-    rpc_client: Client,
+    source: Arc<dyn BlockSource>,
+    conn: DbConnection,
+    genesis: &'static Genesis,
+    prefetch: PrefetchOptions,
+    blocks_dir: Option<PathBuf>,
+    /// First backoff between fetch retries, doubling each time
+    retry_delay: Duration,
+    notifier: Notifier,
 }
 
 impl Indexer {
-    pub fn new() -> Result<Self, bitcoincore_rpc::Error> {
-        let options = BitcoinRpcOptions::new();
-        let rpc_client = options.create_rpc_client()?;
-        Ok(Self { rpc_client })
+    pub fn new() -> Result<Self, ArtifactError> {
+        let options = BitcoinRpcOptions::new()?;
+        let source: Arc<dyn BlockSource> = match options.block_api {
+            BlockApi::Rpc => {
+                let rpc_client = options.create_rpc_client()?;
+                options.check_network(&rpc_client)?;
+                Arc::new(rpc_client)
+            }
+            BlockApi::Rest => {
+                let rest_client = options.create_rest_client()?;
+                options.check_rest_network(&rest_client)?;
+                Arc::new(rest_client)
+            }
+        };
+        // Message senders come from the outputs their transactions spend
+        source::check_spent_scripts(&*source)?;
+        let conn = establish_connection()?;
+        let genesis = Genesis::for_network(options.network)?;
+        let prefetch = PrefetchOptions::new()?;
+        let notifier = Notifier::new(&NotifyOptions::new()?)?;
+
+        let mut indexer = Self::with_source(conn, genesis, source, prefetch).with_notifier(notifier);
+        indexer.blocks_dir = options.blocks_dir;
+        Ok(indexer)
+    }
+
+    /// Indexer reading from any block source (no block files)
+    pub fn with_source(
+        conn: DbConnection,
+        genesis: &'static Genesis,
+        source: Arc<dyn BlockSource>,
+        prefetch: PrefetchOptions,
+    ) -> Self {
+        Self {
+            source,
+            conn,
+            genesis,
+            prefetch,
+            blocks_dir: None,
+            retry_delay: Duration::from_secs(2),
+            notifier: Notifier::polling(Duration::from_secs(30)).0,
+        }
     }
 
-    pub fn fetch_latest_block(&self) -> bitcoincore_rpc::Result<BlockData> {
-        let best_block_hash = self.rpc_client.get_best_block_hash()?;
-        let block = self.rpc_client.get_block(&best_block_hash)?;
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    pub fn fetch_latest_block(&self) -> Result<BlockData, ArtifactError> {
+        let tip = self.source.tip()?;
+        let block = self.source.block(&tip.hash)?;
 
         Ok(BlockData { block })
     }
 
-    pub fn log_blocks_and_txids(&self) -> Result<(), String> {
-        let mut height = 0;
-        let mut retries = 0;
+    pub fn index_blocks(&mut self) -> Result<(), ArtifactError> {
+        check_network(&mut self.conn, self.genesis.network)?;
+
+        // Resume after the last committed block
+        let mut height = next_height(&mut self.conn, self.genesis)?;
+
+        // Catch up from the block files, then follow the node
+        if let Some(blocks_dir) = &self.blocks_dir {
+            let mut reader = BlockFileReader::open(blocks_dir, self.genesis.network)?;
+            // Only blocks the node validated, it follows from there
+            reader.truncate_to(&*self.source)?;
+            println!("Reading block files up to {:?}", reader.tip().ok().map(|tip| tip.height));
+
+            height = self.sync_from(height, Arc::new(reader))?;
+        }
+
+        self.sync_from(height, Arc::clone(&self.source))?;
+
+        Ok(())
+    }
+
+    /// Catch up, then index new blocks as the notifier announces them
+    /// (returns only on error)
+    pub fn follow(&mut self) -> Result<(), ArtifactError> {
+        self.index_blocks()?;
 
         loop {
-            match self.fetch_block_with_retries(height) {
-                Ok(Some(block)) => {
-                    println!("Block number: {}", height);
-                    for txid in block.txdata.iter().map(|tx| tx.txid()) {
-                        println!("TxID: {}", txid);
-                    }
-                    height += 1;
-                    retries = 0; // Reset retry count after a successful fetch
+            let announced = self.notifier.wait(|tx| {
+                if tx.output.iter().any(|output| protocol::envelope(&output.script_pubkey).is_some()) {
+                    println!("Unconfirmed Artifact transaction {}", tx.txid());
+                }
+            });
+            if let Some(hash) = announced {
+                println!("Block announced: {}", hash);
+            }
+
+            let height = next_height(&mut self.conn, self.genesis)?;
+            self.sync_from(height, Arc::clone(&self.source))?;
+        }
+    }
+
+    /// Index blocks from `source` until it runs out, returning the next
+    /// height. The node decides reorgs and reports spent outputs.
+    fn sync_from(&mut self, mut height: u32, source: Arc<dyn BlockSource>) -> Result<u32, ArtifactError> {
+        'pipeline: loop {
+            let (fetch_source, retry_delay) = (Arc::clone(&source), self.retry_delay);
+            let mut prefetcher = Prefetcher::start(height, &self.prefetch, move |h| {
+                fetch_block_with_retries(&*fetch_source, h, retry_delay)
+            });
+
+            // No more blocks to fetch
+            while let Some(block) = prefetcher.next_block()? {
+                println!("Block number: {}", height);
+                let next = sync_block(&mut self.conn, self.genesis, height, &block, &*self.source)?;
+
+                // Rolled back: blocks prefetched above the fork are stale
+                if next != height + 1 {
+                    height = next;
+                    continue 'pipeline;
                 }
-                Ok(None) => break, // No more blocks to fetch
-                Err(e) => {
-                    retries += 1;
-                    if retries > 5 {
-                        return Err(format!("Error: Too many retries, aborting. Last error: {:?}", e));
-                    }
-                    let wait_time = 2u64.pow(retries);
-                    eprintln!("Retry {retries}: waiting {wait_time} seconds...");
-                    thread::sleep(Duration::from_secs(wait_time));
+                height = next;
+            }
+
+            return Ok(height);
+        }
+    }
+}
+
+/// Fetch the block at `height` (`None` past the source's tip), backing off
+/// between retries
+fn fetch_block_with_retries(
+    source: &dyn BlockSource,
+    height: u32,
+    retry_delay: Duration,
+) -> Result<Option<Block>, ArtifactError> {
+    let mut retries = 0;
+
+    loop {
+        match source.block_at(height) {
+            Ok(block) => return Ok(block),
+            Err(e) => {
+                retries += 1;
+                if retries > 5 {
+                    eprintln!("Error: Too many retries for block {height}, aborting.");
+                    return Err(e);
                 }
+                let wait_time = retry_delay * 2u32.pow(retries - 1);
+                eprintln!("Retry {retries} for block {height}: waiting {wait_time:?}...");
+                thread::sleep(wait_time);
             }
         }
+    }
+}
+
+/// Height after the last committed block (the protocol start height on
+/// a fresh ledger)
+pub fn next_height(conn: &mut DbConnection, genesis: &Genesis) -> Result<u32, ArtifactError> {
+    let last_block = fetch_last_block(conn)?;
+
+    Ok(last_block.map(|b| b.height as u32 + 1).unwrap_or(genesis.start_height as u32))
+}
+
+/// Index `block` at `height` if it extends the committed chain, otherwise
+/// roll back to where it forks from `source`'s best chain. Returns the
+/// next height to fetch. The genesis state is applied with the first block,
+/// and `source` reports the outputs spent by blocks carrying messages.
+pub fn sync_block(
+    conn: &mut DbConnection,
+    genesis: &Genesis,
+    height: u32,
+    block: &Block,
+    source: &dyn BlockSource,
+) -> Result<u32, ArtifactError> {
+    let tip = fetch_last_block(conn)?;
+
+    if let Some(tip) = &tip {
+        if tip.hash != block.header.prev_blockhash.to_string() {
+            let fork = find_fork(conn, tip.height, source)?;
+            println!("Reorg at {}: rolling back to {:?}", height, fork);
+
+            rollback(conn, genesis, fork)?;
+            return Ok(fork.map(|f| f as u32 + 1).unwrap_or(genesis.start_height as u32));
+        }
+    }
+
+    // Only message senders need their spent outputs
+    let has_messages = block
+        .txdata
+        .iter()
+        .any(|tx| tx.output.iter().any(|output| protocol::envelope(&output.script_pubkey).is_some()));
+    let spent = if has_messages {
+        source.spent_scripts(block)?
+    } else {
+        HashMap::new()
+    };
+
+    conn.transaction(|conn| {
+        if tip.is_none() {
+            genesis.apply(conn)?;
+        }
+
+        index_block(conn, genesis, height as i32, block, &spent)?;
+
+        let ledger_hash = compute_ledger_hash(conn, genesis, height as i32)?;
+
+        create_block(
+            conn,
+            &(height as i32),
+            &block.block_hash().to_string(),
+            &block.header.prev_blockhash.to_string(),
+            &ledger_hash,
+        )
+    })?;
+
+    Ok(height + 1)
+}
+
+/// Highest committed block still on the canonical chain
+fn find_fork(conn: &mut DbConnection, tip_height: i32, source: &dyn BlockSource) -> Result<Option<i32>, ArtifactError> {
+    for height in (0..=tip_height).rev() {
+        let committed = fetch_block(conn, height)?;
+
+        if let Some(committed) = committed {
+            // Above a shorter canonical chain's tip counts as stale
+            if Some(committed.hash) == source.block_hash(height as u32)?.map(|hash| hash.to_string()) {
+                return Ok(Some(height));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Roll the ledger back to `fork` (or to empty), undoing what was written
+/// above it
+pub fn rollback(conn: &mut DbConnection, genesis: &Genesis, fork: Option<i32>) -> Result<(), ArtifactError> {
+    let height = fork.unwrap_or(-1);
+
+    conn.transaction(|conn| {
+        match fork {
+            Some(height) => revert_ledger(conn, genesis, height)?,
+            // An empty ledger gets its genesis with the next first block
+            None => reset_ledger(conn, genesis)?,
+        }
+
+        delete_rejections(conn, height)?;
+        delete_blocks_after(conn, height)?;
+        with_backend!(conn, |conn| {
+            diesel::delete(messages::table.filter(messages::block_height.gt(height))).execute(conn)
+        })?;
 
         Ok(())
+    })
+}
+
+/// Undo the ledger events above `height`: balances, supplies, token flags,
+/// owners and address flags go back to what they were at `height`
+fn revert_ledger(conn: &mut DbConnection, genesis: &Genesis, height: i32) -> Result<(), ArtifactError> {
+    // Credits come back out of balances, debits go back in
+    let mut changes: BTreeMap<(String, String), i64> = BTreeMap::new();
+
+    let credited = with_backend!(conn, |conn| {
+        credits::table
+            .filter(credits::block_height.gt(height))
+            .select((credits::address, credits::token, credits::quantity))
+            .load::<(String, String, i64)>(conn)
+    })?;
+    for (address, token, quantity) in credited {
+        *changes.entry((address, token)).or_insert(0) -= quantity;
     }
 
-    fn fetch_block_with_retries(&self, height: u32) -> Result<Option<Block>, String> {
-        match self.rpc_client.get_block_hash(height.into()) {
-            Ok(hash) => {
-                match self.rpc_client.get_block(&hash) {
-                    Ok(block) => Ok(Some(block)),
-                    Err(e) => {
-                        eprintln!("Error fetching block: {:?}", e);
-                        Ok(None)
-                    }
-                }
+    let debited = with_backend!(conn, |conn| {
+        debits::table
+            .filter(debits::block_height.gt(height))
+            .select((debits::address, debits::token, debits::quantity))
+            .load::<(String, String, i64)>(conn)
+    })?;
+    for (address, token, quantity) in debited {
+        *changes.entry((address, token)).or_insert(0) += quantity;
+    }
+
+    for ((address, token), change) in changes {
+        let quantity = fetch_quantity(conn, &address, &token)? + change;
+        upsert_balance(conn, &address, &token, &quantity)?;
+    }
+
+    // Tokens first issued above the fork go away entirely
+    let created = with_backend!(conn, |conn| {
+        tokens::table
+            .filter(tokens::issued_at_height.gt(height))
+            .select(tokens::token)
+            .load::<String>(conn)
+    })?;
+
+    // Reissued tokens lose the supply, and get back the flags, of before
+    let reissued = with_backend!(conn, |conn| {
+        issuances::table
+            .filter(issuances::block_height.gt(height))
+            .filter(issuances::token.ne_all(&created))
+            .select((issuances::token, issuances::quantity))
+            .load::<(String, i64)>(conn)
+    })?;
+    let mut reissued_supply: BTreeMap<String, i64> = BTreeMap::new();
+    for (token, quantity) in reissued {
+        *reissued_supply.entry(token).or_insert(0) += quantity;
+    }
+
+    for (token_name, quantity) in reissued_supply {
+        let current = token::fetch_token(conn, &token_name)?
+            .ok_or_else(|| ArtifactError::Configuration(format!("Reissued token {} is missing", token_name)))?;
+        let surviving = with_backend!(conn, |conn| {
+            issuances::table
+                .filter(issuances::token.eq(&token_name))
+                .filter(issuances::block_height.le(height))
+                .order(issuances::id.desc())
+                .select(issuances::flags)
+                .first::<i32>(conn)
+                .optional()
+        })?;
+        let flags = surviving
+            .or_else(|| genesis.tokens.iter().find(|t| t.token == token_name).map(|t| t.flags))
+            .ok_or_else(|| ArtifactError::Configuration(format!("No issuance of {} below {}", token_name, height)))?;
+
+        token::update_supply(conn, &token_name, &(current.total_supply - quantity))?;
+        token::update_token(conn, &token_name, &flags)?;
+    }
+
+    // Owners go back to the sender of the first transfer above the fork
+    // (collected last to first, so the first one wins)
+    let transferred = with_backend!(conn, |conn| {
+        ownership_transfers::table
+            .filter(ownership_transfers::block_height.gt(height))
+            .filter(ownership_transfers::token.ne_all(&created))
+            .order(ownership_transfers::id.desc())
+            .select((ownership_transfers::token, ownership_transfers::source))
+            .load::<(String, String)>(conn)
+    })?;
+    let previous_owners: BTreeMap<String, String> = transferred.into_iter().collect();
+    for (token_name, owner) in previous_owners {
+        token::update_owner(conn, &token_name, &owner)?;
+    }
+
+    with_backend!(conn, |conn| {
+        diesel::delete(credits::table.filter(credits::block_height.gt(height))).execute(conn)?;
+        diesel::delete(debits::table.filter(debits::block_height.gt(height))).execute(conn)?;
+        diesel::delete(issuances::table.filter(issuances::block_height.gt(height))).execute(conn)?;
+        diesel::delete(ownership_transfers::table.filter(ownership_transfers::block_height.gt(height))).execute(conn)?;
+        diesel::delete(balances::table.filter(balances::token.eq_any(&created))).execute(conn)?;
+        diesel::delete(tokens::table.filter(tokens::token.eq_any(&created))).execute(conn)
+    })?;
+
+    revert_address_flags(conn, height)
+}
+
+/// Address flags as of `height`, from the last journaled change at or
+/// below it (none means never set)
+fn revert_address_flags(conn: &mut DbConnection, height: i32) -> Result<(), ArtifactError> {
+    let changed = with_backend!(conn, |conn| {
+        address_events::table
+            .filter(address_events::block_height.gt(height))
+            .select(address_events::address)
+            .distinct()
+            .load::<String>(conn)
+    })?;
+
+    for address_name in changed {
+        let surviving = with_backend!(conn, |conn| {
+            address_events::table
+                .filter(address_events::address.eq(&address_name))
+                .filter(address_events::block_height.le(height))
+                .order(address_events::id.desc())
+                .select((address_events::flags, address_events::unlock_height))
+                .first::<(i32, Option<i32>)>(conn)
+                .optional()
+        })?;
+        let (flags, unlock_height) = surviving.unwrap_or((0, None));
+
+        address::update_flags(conn, &address_name, Flags::from_bits_truncate(flags), unlock_height)?;
+    }
+
+    with_backend!(conn, |conn| {
+        diesel::delete(address_events::table.filter(address_events::block_height.gt(height))).execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Clear all state derived from messages and the genesis
+fn reset_ledger(conn: &mut DbConnection, genesis: &Genesis) -> Result<(), ArtifactError> {
+    let mut derived = with_backend!(conn, |conn| {
+        issuances::table
+            .select(issuances::token)
+            .distinct()
+            .load::<String>(conn)
+    })?;
+    derived.extend(genesis.reserved.iter().map(|name| name.to_string()));
+    derived.extend(genesis.tokens.iter().map(|token| token.token.to_string()));
+
+    with_backend!(conn, |conn| {
+        diesel::delete(credits::table).execute(conn)?;
+        diesel::delete(debits::table).execute(conn)?;
+        diesel::delete(balances::table).execute(conn)?;
+        diesel::delete(issuances::table).execute(conn)?;
+        diesel::delete(ownership_transfers::table).execute(conn)?;
+        diesel::delete(address_events::table).execute(conn)
+    })?;
+    reset_address_flags(conn)?;
+    with_backend!(conn, |conn| {
+        diesel::delete(tokens::table.filter(tokens::token.eq_any(derived))).execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Apply every Artifact message in `block` to the ledger, `spent` holding
+/// the outputs its transactions spend
+pub fn index_block(
+    conn: &mut DbConnection,
+    genesis: &Genesis,
+    height: i32,
+    block: &Block,
+    spent: &HashMap<OutPoint, ScriptBuf>,
+) -> Result<(), ArtifactError> {
+    // Delayed unlocks take effect before the block's messages
+    release_unlocks(conn, height)?;
+
+    for (tx_index, tx) in block.txdata.iter().enumerate() {
+        let txid = tx.txid().to_string();
+
+        // First Artifact output only
+        let data = match tx.output.iter().find_map(|output| protocol::envelope(&output.script_pubkey)) {
+            Some(data) => data,
+            None => continue,
+        };
+
+        let source = match tx.input.first() {
+            Some(input) if !tx.is_coinbase() => {
+                let spent = spent.get(&input.previous_output).ok_or_else(|| {
+                    ArtifactError::Configuration(format!("Output spent by {} is unknown", txid))
+                })?;
+                protocol::source_address(tx, spent, genesis.network).map(|s| s.to_string())
             }
+            _ => None,
+        };
+        let destination = protocol::destination_address(tx, genesis.network).map(|d| d.to_string());
+        let candidate = Candidate {
+            block_height: height,
+            tx_index: tx_index as i32,
+            txid: &txid,
+            source: source.as_deref(),
+            destination: destination.as_deref(),
+            message_type: data.first().map(|id| *id as i32),
+        };
+
+        let message = match protocol::decode(&data) {
+            Ok(message) => message,
             Err(e) => {
-                eprintln!("Error fetching block hash: {:?}", e);
-                Err(e.to_string())
+                reject(conn, &candidate, None, &e.into())?;
+                continue;
+            }
+        };
+
+        // Message types follow the network's activation heights
+        let activation_height = genesis.activation_height(message.message_type());
+        if height < activation_height {
+            let e = ArtifactError::rejected(
+                Reason::NotActivated,
+                format!("{:?} messages activate at {}", message.message_type(), activation_height),
+            );
+            reject(conn, &candidate, None, &e)?;
+            continue;
+        }
+
+        let source = match source {
+            Some(source) => source,
+            None => {
+                let e = ArtifactError::rejected(Reason::MissingSource, "Unrecognized input type");
+                reject(conn, &candidate, None, &e)?;
+                continue;
             }
+        };
+
+        // Journal the message before applying it
+        let message_id = create_message(
+            conn,
+            &NewMessage {
+                block_height: &height,
+                tx_index: &(tx_index as i32),
+                txid: &txid,
+                source: &source,
+                destination: destination.as_deref(),
+                message_type: &(message.message_type().id() as i32),
+                data: &message.encode(),
+            },
+        )?;
+
+        let origin = Origin {
+            block_height: height,
+            tx_index: tx_index as i32,
+            txid: &txid,
+            message_id: Some(message_id),
+        };
+
+        apply_or_reject(conn, &origin, &source, destination.as_deref(), message)?;
+    }
+
+    Ok(())
+}
+
+/// Apply a message, recording (not failing on) protocol rejections
+fn apply_or_reject(
+    conn: &mut DbConnection,
+    origin: &Origin,
+    source: &str,
+    destination: Option<&str>,
+    message: Message,
+) -> Result<(), ArtifactError> {
+    let candidate = Candidate {
+        block_height: origin.block_height,
+        tx_index: origin.tx_index,
+        txid: origin.txid,
+        source: Some(source),
+        destination,
+        message_type: Some(message.message_type().id() as i32),
+    };
+
+    match apply(conn, origin, source, destination, message) {
+        Err(e) if e.is_rejection() => reject(conn, &candidate, origin.message_id, &e),
+        result => result,
+    }
+}
+
+/// Where a rejected message was found
+struct Candidate<'a> {
+    block_height: i32,
+    tx_index: i32,
+    txid: &'a str,
+    source: Option<&'a str>,
+    destination: Option<&'a str>,
+    message_type: Option<i32>,
+}
+
+/// Record why a message was rejected
+fn reject(
+    conn: &mut DbConnection,
+    candidate: &Candidate,
+    message_id: Option<i32>,
+    error: &ArtifactError,
+) -> Result<(), ArtifactError> {
+    println!("Rejected {}: {}", candidate.txid, error);
+
+    create_rejection(
+        conn,
+        &NewRejection {
+            block_height: &candidate.block_height,
+            tx_index: &candidate.tx_index,
+            txid: candidate.txid,
+            source: candidate.source,
+            destination: candidate.destination,
+            message_type: candidate.message_type.as_ref(),
+            reason_code: &(error.code() as i32),
+            reason: &error.to_string(),
+            message_id: message_id.as_ref(),
+        },
+    )
+}
+
+/// Apply a single message to the ledger
+fn apply(
+    conn: &mut DbConnection,
+    origin: &Origin,
+    source: &str,
+    destination: Option<&str>,
+    message: Message,
+) -> Result<(), ArtifactError> {
+    match message {
+        Message::Send(message) => match destination {
+            Some(destination) => send::parse(
+                conn,
+                origin,
+                source,
+                destination,
+                message.token_id,
+                message.quantity,
+                message.memo.as_deref(),
+            ),
+            None => Err(ArtifactError::rejected(Reason::MissingDestination, "No output to credit")),
+        },
+        Message::Issuance(issuance) => token::parse(
+            conn,
+            origin,
+            source,
+            issuance.token_id,
+            issuance.quantity,
+            issuance.divisibility,
+            issuance.flags,
+        ),
+        Message::TransferOwnership(transfer) => match destination {
+            Some(destination) => ownership::parse(conn, origin, source, destination, transfer.token_id),
+            None => Err(ArtifactError::rejected(Reason::MissingDestination, "No output to receive ownership")),
+        },
+        Message::LockAddress(_) => address::parse_lock(conn, origin, source),
+        Message::UnlockAddress(_) => address::parse_unlock(conn, origin, source),
+        Message::AddressOptions(options) => match destination {
+            Some(destination) => address::parse_options(conn, origin, source, destination, options.flags),
+            None => Err(ArtifactError::rejected(Reason::MissingDestination, "No output naming the address")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, establish_test_connection};
+    use crate::models::issuance::fetch_issuances;
+    use crate::models::message::fetch_message;
+    use crate::protocol::Issuance;
+    use crate::genesis::MAINNET;
+    use crate::models::balance::fetch_quantity;
+    use crate::models::block::{fetch_block, fetch_ledger_hash};
+    use crate::models::rejection;
+    use crate::models::token::token_exists;
+    use crate::source::MemoryChain;
+    use bitcoin::hashes::Hash;
+    use bitcoin::BlockHash;
+
+    fn issuance_block(prev_blockhash: BlockHash, wallet: u8, token_name: &str) -> Block {
+        let issuance = Message::Issuance(Issuance {
+            token_id: token::generate_id(token_name).unwrap(),
+            quantity: 1_000,
+            divisibility: 0,
+            flags: 0,
+        });
+        let tx = fixtures::transaction(wallet, vec![protocol::encode_script(&issuance)]);
+
+        fixtures::block(prev_blockhash, vec![tx])
+    }
+
+    #[test]
+    fn index_block_applies_issuances() {
+        let mut conn = establish_test_connection();
+        let issuance = Message::Issuance(Issuance {
+            token_id: token::generate_id("ABC").unwrap(),
+            quantity: 0,
+            divisibility: 0,
+            flags: 0,
+        });
+        let tx = fixtures::transaction(1, vec![protocol::encode_script(&issuance)]);
+        let block = fixtures::block(BlockHash::all_zeros(), vec![tx.clone()]);
+
+        index_block(&mut conn, &MAINNET, 7, &block, &fixtures::spent_scripts(&block)).unwrap();
+
+        let issuances = fetch_issuances(&mut conn, "ABC").unwrap();
+
+        // Case: Issuer From Source
+        assert_eq!(issuances[0].issuer, fixtures::address(1).to_string());
+        // Case: Height And Txid
+        assert_eq!(issuances[0].block_height, 7);
+        assert_eq!(issuances[0].txid, tx.txid().to_string());
+        // Case: Message Journaled
+        let journaled = fetch_message(&mut conn, 1).unwrap();
+        assert_eq!(journaled.source, fixtures::address(1).to_string());
+        assert_eq!(journaled.data, issuance.encode());
+    }
+
+    #[test]
+    fn index_block_applies_sends_after_issuance() {
+        let mut conn = establish_test_connection();
+        let send = Message::Send(protocol::SendMessage {
+            token_id: token::generate_id("ABC").unwrap(),
+            quantity: 400,
+            memo: None,
+        });
+        let issued = issuance_block(BlockHash::all_zeros(), 1, "ABC");
+        let sent = fixtures::block(
+            issued.block_hash(),
+            vec![fixtures::transaction(
+                1,
+                vec![protocol::encode_script(&send), fixtures::address(2).script_pubkey()],
+            )],
+        );
+
+        index_block(&mut conn, &MAINNET, 0, &issued, &fixtures::spent_scripts(&issued)).unwrap();
+        index_block(&mut conn, &MAINNET, 1, &sent, &fixtures::spent_scripts(&sent)).unwrap();
+
+        let (sender, receiver) = (fixtures::address(1).to_string(), fixtures::address(2).to_string());
+
+        // Case: Issued Then Sent
+        assert_eq!(fetch_quantity(&mut conn, &sender, "ABC").unwrap(), 600);
+        assert_eq!(fetch_quantity(&mut conn, &receiver, "ABC").unwrap(), 400);
+    }
+
+    #[test]
+    fn index_block_transfers_ownership() {
+        let mut conn = establish_test_connection();
+        let transfer = Message::TransferOwnership(protocol::TransferOwnership {
+            token_id: token::generate_id("ABC").unwrap(),
+        });
+        let issued = issuance_block(BlockHash::all_zeros(), 1, "ABC");
+        let transferred = fixtures::block(
+            issued.block_hash(),
+            vec![fixtures::transaction(
+                1,
+                vec![protocol::encode_script(&transfer), fixtures::address(2).script_pubkey()],
+            )],
+        );
+
+        index_block(&mut conn, &MAINNET, 0, &issued, &fixtures::spent_scripts(&issued)).unwrap();
+        index_block(&mut conn, &MAINNET, 1, &transferred, &fixtures::spent_scripts(&transferred)).unwrap();
+
+        let abc = token::fetch_token(&mut conn, "ABC").unwrap().unwrap();
+
+        // Case: Owner Is Destination
+        assert_eq!(abc.owner, Some(fixtures::address(2).to_string()));
+        // Case: Issuer Unchanged
+        assert_eq!(abc.issuer, Some(fixtures::address(1).to_string()));
+        assert_eq!(abc.total_supply, 1_000);
+    }
+
+    #[test]
+    fn index_block_follows_activation_heights() {
+        use crate::genesis::{Activation, Genesis};
+        use crate::protocol::MessageType;
+
+        const DELAYED: Genesis = Genesis {
+            activations: &[Activation {
+                message_type: MessageType::Issuance,
+                height: 5,
+            }],
+            ..MAINNET
+        };
+        let early = issuance_block(BlockHash::all_zeros(), 1, "ABC");
+        let late = issuance_block(early.block_hash(), 1, "DEF");
+        let mut conn = establish_test_connection();
+
+        index_block(&mut conn, &DELAYED, 4, &early, &fixtures::spent_scripts(&early)).unwrap();
+        index_block(&mut conn, &DELAYED, 5, &late, &fixtures::spent_scripts(&late)).unwrap();
+
+        // Case: Before Activation
+        let rejected = rejection::fetch_rejections_by_txid(&mut conn, &early.txdata[0].txid().to_string()).unwrap();
+        assert_eq!(rejected[0].reason_code, Reason::NotActivated.code() as i32);
+        assert!(!token_exists(&mut conn, "ABC").unwrap());
+        // Case: From Activation
+        assert!(token_exists(&mut conn, "DEF").unwrap());
+    }
+
+    #[test]
+    fn sync_block_commits_sync_state() {
+        let mut conn = establish_test_connection();
+        let genesis = fixtures::block(BlockHash::all_zeros(), vec![]);
+        let next = issuance_block(genesis.block_hash(), 1, "ABC");
+        let canonical = fixtures::chain(vec![]);
+
+        // Case: Fresh Database
+        assert_eq!(next_height(&mut conn, &MAINNET).unwrap(), 0);
+
+        sync_block(&mut conn, &MAINNET, 0, &genesis, &canonical).unwrap();
+        sync_block(&mut conn, &MAINNET, 1, &next, &canonical).unwrap();
+
+        // Case: Resume After Tip
+        assert_eq!(next_height(&mut conn, &MAINNET).unwrap(), 2);
+        // Case: Ledger Hashes Chained
+        let genesis_hash = fetch_ledger_hash(&mut conn, 0).unwrap().unwrap();
+        let next_hash = fetch_ledger_hash(&mut conn, 1).unwrap().unwrap();
+        assert_eq!(genesis_hash, crate::consensus::ledger_hash(&MAINNET.ledger_hash(), &[]));
+        assert_ne!(next_hash, crate::consensus::ledger_hash(&genesis_hash, &[]));
+        assert_eq!(fetch_block(&mut conn, 1).unwrap().unwrap().hash, next.block_hash().to_string());
+    }
+
+    #[test]
+    fn sync_block_rolls_back_reorgs() {
+        let mut conn = establish_test_connection();
+        let genesis = fixtures::block(BlockHash::all_zeros(), vec![]);
+
+        // Original branch
+        let a1 = issuance_block(genesis.block_hash(), 1, "ABC");
+        let a2 = issuance_block(a1.block_hash(), 1, "DEF");
+        // Competing branch from genesis
+        let b1 = issuance_block(genesis.block_hash(), 2, "XYZ");
+        let b2 = fixtures::block(b1.block_hash(), vec![]);
+        let b3 = fixtures::block(b2.block_hash(), vec![]);
+
+        let a_chain = fixtures::chain(vec![genesis.clone(), a1.clone(), a2.clone()]);
+        for (height, block) in [&genesis, &a1, &a2].into_iter().enumerate() {
+            sync_block(&mut conn, &MAINNET, height as u32, block, &a_chain).unwrap();
+        }
+
+        let a_ledger_hash = fetch_ledger_hash(&mut conn, 1).unwrap().unwrap();
+
+        let canonical = fixtures::chain(vec![genesis.clone(), b1.clone(), b2.clone(), b3.clone()]);
+
+        // Case: Rolled Back To Genesis
+        assert_eq!(sync_block(&mut conn, &MAINNET, 3, &b3, &canonical).unwrap(), 1);
+        assert!(!token_exists(&mut conn, "ABC").unwrap());
+        assert!(!token_exists(&mut conn, "DEF").unwrap());
+        assert_eq!(next_height(&mut conn, &MAINNET).unwrap(), 1);
+
+        for (height, block) in [&b1, &b2, &b3].into_iter().enumerate() {
+            sync_block(&mut conn, &MAINNET, height as u32 + 1, block, &canonical).unwrap();
+        }
+
+        // Case: New Branch Applied
+        assert!(token_exists(&mut conn, "XYZ").unwrap());
+        assert_ne!(
+            fetch_ledger_hash(&mut conn, 1).unwrap(),
+            Some(a_ledger_hash),
+        );
+        assert_eq!(fetch_block(&mut conn, 3).unwrap().unwrap().hash, b3.block_hash().to_string());
+        assert_eq!(fetch_issuances(&mut conn, "XYZ").unwrap()[0].block_height, 1);
+    }
+
+    fn memory_indexer(chain: &Arc<MemoryChain>) -> Indexer {
+        let prefetch = PrefetchOptions {
+            workers: 2,
+            queue_depth: 4,
+        };
+
+        Indexer::with_source(establish_test_connection(), &MAINNET, Arc::clone(chain) as Arc<dyn BlockSource>, prefetch)
+            .with_retry_delay(Duration::from_millis(1))
+    }
+
+    #[test]
+    fn index_blocks_follows_reorgs() {
+        let genesis = fixtures::block(BlockHash::all_zeros(), vec![]);
+        let a1 = issuance_block(genesis.block_hash(), 1, "ABC");
+        let a2 = issuance_block(a1.block_hash(), 1, "DEF");
+        let b1 = issuance_block(genesis.block_hash(), 2, "XYZ");
+        let b2 = fixtures::block(b1.block_hash(), vec![]);
+        let b3 = fixtures::block(b2.block_hash(), vec![]);
+
+        let chain = Arc::new(fixtures::chain(vec![genesis, a1, a2.clone()]));
+        let mut indexer = memory_indexer(&chain);
+
+        indexer.index_blocks().unwrap();
+
+        // Case: Caught Up
+        assert_eq!(next_height(&mut indexer.conn, &MAINNET).unwrap(), 3);
+        assert!(token_exists(&mut indexer.conn, "DEF").unwrap());
+        assert_eq!(indexer.fetch_latest_block().unwrap().block, a2);
+
+        chain.reorg(1, vec![b1, b2, b3.clone()]);
+        indexer.index_blocks().unwrap();
+
+        // Case: Switched Branch
+        assert_eq!(next_height(&mut indexer.conn, &MAINNET).unwrap(), 4);
+        assert!(!token_exists(&mut indexer.conn, "ABC").unwrap());
+        assert!(token_exists(&mut indexer.conn, "XYZ").unwrap());
+        assert_eq!(fetch_block(&mut indexer.conn, 3).unwrap().unwrap().hash, b3.block_hash().to_string());
+    }
+
+    #[test]
+    fn index_blocks_retries_failed_fetches() {
+        let genesis = fixtures::block(BlockHash::all_zeros(), vec![]);
+        let a1 = issuance_block(genesis.block_hash(), 1, "ABC");
+        let a2 = fixtures::block(a1.block_hash(), vec![]);
+
+        let chain = Arc::new(fixtures::chain(vec![genesis, a1]));
+        let mut indexer = memory_indexer(&chain);
+
+        // Case: Transient Failures
+        chain.fail_next(3);
+        indexer.index_blocks().unwrap();
+        assert_eq!(next_height(&mut indexer.conn, &MAINNET).unwrap(), 2);
+
+        // Case: Source Stays Down
+        chain.extend(vec![a2]);
+        chain.fail_next(100);
+        assert!(indexer.index_blocks().is_err());
+        assert_eq!(next_height(&mut indexer.conn, &MAINNET).unwrap(), 2);
+    }
+
+    #[test]
+    fn sync_block_starts_from_genesis() {
+        use crate::genesis::{Allocation, Genesis, GenesisToken};
+
+        const ALICE: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+        const FUNDED: Genesis = Genesis {
+            start_height: 5,
+            tokens: &[GenesisToken {
+                token: "GEN",
+                flags: 0,
+                divisibility: 0,
+                owner: ALICE,
+            }],
+            allocations: &[Allocation {
+                address: ALICE,
+                token: "GEN",
+                quantity: 500,
+            }],
+            ..MAINNET
+        };
+        let mut conn = establish_test_connection();
+        let first = fixtures::block(BlockHash::all_zeros(), vec![]);
+        let second = issuance_block(first.block_hash(), 1, "ABC");
+
+        // Case: Fresh Ledger Starts At Start Height
+        assert_eq!(next_height(&mut conn, &FUNDED).unwrap(), 5);
+
+        sync_block(&mut conn, &FUNDED, 5, &first, &fixtures::chain(vec![])).unwrap();
+        sync_block(&mut conn, &FUNDED, 6, &second, &fixtures::chain(vec![])).unwrap();
+
+        // Case: Genesis Applied
+        assert_eq!(fetch_quantity(&mut conn, ALICE, "GEN").unwrap(), 500);
+        assert!(token_exists(&mut conn, "XBT").unwrap());
+        // Case: Genesis Hashed
+        assert_eq!(
+            fetch_ledger_hash(&mut conn, 5).unwrap().unwrap(),
+            crate::consensus::ledger_hash(&FUNDED.ledger_hash(), &[format!("credit|genesis|genesis|{}|GEN|500", ALICE)])
+        );
+
+        rollback(&mut conn, &FUNDED, Some(5)).unwrap();
+
+        // Case: Genesis Survives Rollback
+        assert_eq!(fetch_quantity(&mut conn, ALICE, "GEN").unwrap(), 500);
+        assert!(!token_exists(&mut conn, "ABC").unwrap());
+
+        rollback(&mut conn, &FUNDED, None).unwrap();
+        sync_block(&mut conn, &FUNDED, 5, &first, &fixtures::chain(vec![])).unwrap();
+
+        // Case: Reapplied Once After Full Rollback
+        assert_eq!(fetch_quantity(&mut conn, ALICE, "GEN").unwrap(), 500);
+    }
+
+    #[test]
+    fn rollback_keeps_surviving_messages() {
+        let mut conn = establish_test_connection();
+        let genesis = fixtures::block(BlockHash::all_zeros(), vec![]);
+        let a1 = issuance_block(genesis.block_hash(), 1, "ABC");
+        let a2 = issuance_block(a1.block_hash(), 1, "DEF");
+
+        for (height, block) in [&genesis, &a1, &a2].into_iter().enumerate() {
+            sync_block(&mut conn, &MAINNET, height as u32, block, &fixtures::chain(vec![])).unwrap();
+        }
+
+        rollback(&mut conn, &MAINNET, Some(1)).unwrap();
+
+        // Case: Kept Below Fork
+        assert!(token_exists(&mut conn, "ABC").unwrap());
+        assert_eq!(fetch_issuances(&mut conn, "ABC").unwrap().len(), 1);
+        // Case: Removed Above Fork
+        assert!(!token_exists(&mut conn, "DEF").unwrap());
+        assert_eq!(next_height(&mut conn, &MAINNET).unwrap(), 2);
+    }
+
+    #[test]
+    fn rollback_reverts_ledger_events_above_fork() {
+        let mut conn = establish_test_connection();
+        let token_id = token::generate_id("ABC").unwrap();
+        let send = Message::Send(protocol::SendMessage {
+            token_id,
+            quantity: 400,
+            memo: None,
+        });
+        let reissue = Message::Issuance(Issuance {
+            token_id,
+            quantity: 500,
+            divisibility: 0,
+            flags: token::Flags::LOCKED.bits(),
+        });
+        let transfer = Message::TransferOwnership(protocol::TransferOwnership { token_id });
+        let a0 = issuance_block(BlockHash::all_zeros(), 1, "ABC");
+        let a1 = fixtures::block(
+            a0.block_hash(),
+            vec![
+                fixtures::transaction(1, vec![protocol::encode_script(&send), fixtures::address(2).script_pubkey()]),
+                fixtures::transaction(1, vec![protocol::encode_script(&reissue)]),
+                fixtures::transaction(1, vec![protocol::encode_script(&transfer), fixtures::address(3).script_pubkey()]),
+            ],
+        );
+        let a2 = issuance_block(a1.block_hash(), 2, "DEF");
+        let (alice, bob) = (fixtures::address(1).to_string(), fixtures::address(2).to_string());
+
+        for (height, block) in [&a0, &a1, &a2].into_iter().enumerate() {
+            sync_block(&mut conn, &MAINNET, height as u32, block, &fixtures::chain(vec![])).unwrap();
+        }
+        let a1_hash = fetch_ledger_hash(&mut conn, 1).unwrap();
+
+        rollback(&mut conn, &MAINNET, Some(0)).unwrap();
+
+        // Case: Balances Reverted
+        assert_eq!(fetch_quantity(&mut conn, &alice, "ABC").unwrap(), 1_000);
+        assert_eq!(fetch_quantity(&mut conn, &bob, "ABC").unwrap(), 0);
+        // Case: Supply, Flags And Owner Reverted
+        let abc = token::fetch_token(&mut conn, "ABC").unwrap().unwrap();
+        assert_eq!(abc.total_supply, 1_000);
+        assert_eq!(abc.flags, 0);
+        assert_eq!(abc.owner, Some(alice.clone()));
+        // Case: Tokens Issued Above Fork Removed
+        assert!(!token_exists(&mut conn, "DEF").unwrap());
+        // Case: Journal Agrees With Balances
+        assert!(crate::models::balance::audit_balances(&mut conn).unwrap().is_empty());
+
+        sync_block(&mut conn, &MAINNET, 1, &a1, &fixtures::chain(vec![])).unwrap();
+
+        // Case: Reindexed Like The First Time
+        assert_eq!(fetch_ledger_hash(&mut conn, 1).unwrap(), a1_hash);
+        assert_eq!(fetch_quantity(&mut conn, &alice, "ABC").unwrap(), 1_100);
+    }
+
+    #[test]
+    fn rollback_restores_address_locks() {
+        let mut conn = establish_test_connection();
+        let wallet = fixtures::address(1).to_string();
+        let lock = fixtures::transaction(1, vec![protocol::encode_script(&Message::LockAddress(protocol::LockAddress))]);
+        let unlock = fixtures::transaction(1, vec![protocol::encode_script(&Message::UnlockAddress(protocol::UnlockAddress))]);
+        let a0 = fixtures::block(BlockHash::all_zeros(), vec![lock]);
+        let a1 = fixtures::block(a0.block_hash(), vec![unlock]);
+
+        for (height, block) in [&a0, &a1].into_iter().enumerate() {
+            sync_block(&mut conn, &MAINNET, height as u32, block, &fixtures::chain(vec![])).unwrap();
+        }
+
+        // Case: Unlock Scheduled
+        let locked = address::fetch_address(&mut conn, &wallet).unwrap().unwrap();
+        assert_eq!(locked.unlock_height, Some(1 + address::UNLOCK_DELAY));
+        // Case: Unlock Hashed With Its Block
+        let unlock_event = format!("address|{}|unlock|{}|1|{}", a1.txdata[0].txid(), wallet, 1 + address::UNLOCK_DELAY);
+        assert_eq!(crate::consensus::block_events(&mut conn, 1).unwrap(), vec![unlock_event]);
+
+        rollback(&mut conn, &MAINNET, Some(0)).unwrap();
+
+        // Case: Lock Survives, Unlock Removed
+        let locked = address::fetch_address(&mut conn, &wallet).unwrap().unwrap();
+        assert!(address::validate_unlocked(&mut conn, &wallet).is_err());
+        assert_eq!(locked.unlock_height, None);
+        assert!(crate::consensus::block_events(&mut conn, 1).unwrap().is_empty());
+        assert_eq!(address::fetch_address_events(&mut conn, &wallet).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rollback_restores_address_options() {
+        let mut conn = establish_test_connection();
+        let wallet = fixtures::address(1).to_string();
+        let options = |flags: address::Flags| {
+            let message = Message::AddressOptions(protocol::AddressOptions { flags: flags.bits() });
+            fixtures::transaction(1, vec![protocol::encode_script(&message), fixtures::address(1).script_pubkey()])
+        };
+        let a0 = fixtures::block(BlockHash::all_zeros(), vec![options(address::Flags::MEMOFIELD)]);
+        let a1 = fixtures::block(a0.block_hash(), vec![options(address::Flags::empty())]);
+
+        for (height, block) in [&a0, &a1].into_iter().enumerate() {
+            sync_block(&mut conn, &MAINNET, height as u32, block, &fixtures::chain(vec![])).unwrap();
+        }
+
+        // Case: Options Hashed With Their Block
+        let options_event = format!("address|{}|options|{}|0|", a1.txdata[0].txid(), wallet);
+        assert_eq!(crate::consensus::block_events(&mut conn, 1).unwrap(), vec![options_event]);
+        assert_eq!(address::fetch_address(&mut conn, &wallet).unwrap().unwrap().flags, 0);
+
+        rollback(&mut conn, &MAINNET, Some(0)).unwrap();
+
+        // Case: Flags Of The Fork Restored
+        let memo = address::Flags::MEMOFIELD.bits();
+        assert_eq!(address::fetch_address(&mut conn, &wallet).unwrap().unwrap().flags, memo);
+        assert!(crate::consensus::block_events(&mut conn, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn index_block_records_rejections() {
+        let mut conn = establish_test_connection();
+        let send = Message::Send(protocol::SendMessage {
+            token_id: token::generate_id("ABC").unwrap(),
+            quantity: 5,
+            memo: None,
+        });
+        let unsent = fixtures::transaction(1, vec![protocol::encode_script(&send), fixtures::address(2).script_pubkey()]);
+        let truncated = bitcoin::script::PushBytesBuf::try_from(b"ART\x00\x01".to_vec()).unwrap();
+        let malformed = fixtures::transaction(3, vec![bitcoin::ScriptBuf::new_op_return(truncated)]);
+        let block = fixtures::block(BlockHash::all_zeros(), vec![unsent.clone(), malformed.clone()]);
+
+        index_block(&mut conn, &MAINNET, 4, &block, &fixtures::spent_scripts(&block)).unwrap();
+
+        let (sender, receiver) = (fixtures::address(1).to_string(), fixtures::address(2).to_string());
+        let by_txid = rejection::fetch_rejections_by_txid(&mut conn, &unsent.txid().to_string()).unwrap();
+
+        // Case: Ledger Rejection
+        assert_eq!(by_txid.len(), 1);
+        assert_eq!(by_txid[0].reason_code, Reason::UnknownToken.code() as i32);
+        assert!(by_txid[0].reason.starts_with("UnknownToken"));
+        assert_eq!(by_txid[0].block_height, 4);
+        assert_eq!(by_txid[0].message_type, Some(0));
+        assert_eq!(by_txid[0].message_id, Some(1));
+        // Case: By Sender Or Receiver
+        assert_eq!(rejection::fetch_rejections_by_address(&mut conn, &sender).unwrap().len(), 1);
+        assert_eq!(rejection::fetch_rejections_by_address(&mut conn, &receiver).unwrap().len(), 1);
+        // Case: Decode Rejection
+        let by_txid = rejection::fetch_rejections_by_txid(&mut conn, &malformed.txid().to_string()).unwrap();
+        assert_eq!(by_txid[0].source, Some(fixtures::address(3).to_string()));
+        assert_eq!(by_txid[0].message_id, None);
+        assert!(by_txid[0].reason.starts_with("InvalidPayloadLength"));
+
+        rollback(&mut conn, &MAINNET, Some(4)).unwrap();
+
+        // Case: Kept Below The Fork
+        assert_eq!(rejection::fetch_rejections_by_address(&mut conn, &sender).unwrap().len(), 1);
+        assert_eq!(rejection::fetch_rejections_by_txid(&mut conn, &malformed.txid().to_string()).unwrap().len(), 1);
+
+        rollback(&mut conn, &MAINNET, Some(3)).unwrap();
+
+        // Case: Removed On Rollback
+        assert!(rejection::fetch_rejections_by_address(&mut conn, &sender).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn postgres_matches_sqlite_ledger_hashes() {
+        let Some(mut postgres) = fixtures::establish_test_postgres_connection() else {
+            return;
+        };
+        let mut sqlite = establish_test_connection();
+
+        let send = Message::Send(protocol::SendMessage {
+            token_id: token::generate_id("ABC").unwrap(),
+            quantity: 250,
+            memo: Some("invoice|7".to_string()),
+        });
+        let transfer = Message::TransferOwnership(protocol::TransferOwnership {
+            token_id: token::generate_id("ABC").unwrap(),
+        });
+        let genesis = fixtures::block(BlockHash::all_zeros(), vec![]);
+        let issued = issuance_block(genesis.block_hash(), 1, "ABC");
+        let moved = fixtures::block(
+            issued.block_hash(),
+            vec![
+                fixtures::transaction(1, vec![protocol::encode_script(&send), fixtures::address(2).script_pubkey()]),
+                fixtures::transaction(3, vec![protocol::encode_script(&send), fixtures::address(2).script_pubkey()]),
+                fixtures::transaction(1, vec![protocol::encode_script(&transfer), fixtures::address(3).script_pubkey()]),
+            ],
+        );
+        let chain = [&genesis, &issued, &moved];
+
+        for conn in [&mut sqlite, &mut postgres] {
+            for (height, block) in chain.into_iter().enumerate() {
+                sync_block(conn, &MAINNET, height as u32, block, &fixtures::chain(vec![])).unwrap();
+            }
+            rollback(conn, &MAINNET, Some(1)).unwrap();
+            sync_block(conn, &MAINNET, 2, &moved, &fixtures::chain(vec![])).unwrap();
+        }
+
+        // Case: Identical Hashes
+        for height in 0..chain.len() as i32 {
+            let expected = fetch_ledger_hash(&mut sqlite, height).unwrap();
+            assert!(expected.is_some());
+            assert_eq!(fetch_ledger_hash(&mut postgres, height).unwrap(), expected);
         }
+        // Case: Identical Rejections
+        let receiver = fixtures::address(2).to_string();
+        assert_eq!(
+            rejection::fetch_rejections_by_address(&mut postgres, &receiver).unwrap().len(),
+            rejection::fetch_rejections_by_address(&mut sqlite, &receiver).unwrap().len(),
+        );
     }
 }
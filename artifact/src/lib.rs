@@ -1,19 +1,39 @@
 #[macro_use]
 extern crate bitflags;
 
+pub mod blockfile;
+pub mod consensus;
+pub mod database;
+pub mod error;
+pub mod genesis;
+pub mod idn;
+pub mod migrations;
 pub mod models;
 pub mod schema;
+pub mod source;
 pub mod options;
+pub mod notify;
+pub mod prefetch;
 pub mod indexer;
+pub mod protocol;
+pub mod store;
 
-use diesel::prelude::*;
-use dotenvy::dotenv;
-use std::env;
+#[cfg(test)]
+mod fixtures;
 
-pub fn establish_connection() -> SqliteConnection {
-    dotenv().ok();
+use options::DatabaseOptions;
 
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    SqliteConnection::establish(&database_url)
-        .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
+pub use database::DbConnection;
+pub use error::{ArtifactError, Reason};
+
+pub fn establish_connection() -> Result<DbConnection, ArtifactError> {
+    let options = DatabaseOptions::new();
+
+    let database_url = options
+        .database_url
+        .ok_or_else(|| ArtifactError::Configuration("DATABASE_URL must be set".to_string()))?;
+    let mut conn = DbConnection::establish(&database_url)?;
+    migrations::prepare(&mut conn, options.auto_migrate)?;
+
+    Ok(conn)
 }
@@ -0,0 +1,183 @@
+use crate::database::DbConnection;
+use crate::error::ArtifactError;
+use crate::models::block::fetch_last_block;
+use crate::models::ledger_info::{fetch_ledger_info, upsert_ledger_info};
+use crate::protocol::PROTOCOL_VERSION;
+use diesel::backend::Backend;
+use diesel::migration::MigrationSource;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+pub const SQLITE_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+#[cfg(feature = "postgres")]
+pub const POSTGRES_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations_postgres");
+
+/// Bring the schema up to date (or refuse to), then check the database
+/// was indexed under this build's protocol version
+pub fn prepare(conn: &mut DbConnection, auto_migrate: bool) -> Result<(), ArtifactError> {
+    let schema_version = match conn {
+        DbConnection::Sqlite(conn) => migrate(conn, SQLITE_MIGRATIONS, auto_migrate)?,
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(conn) => migrate(conn, POSTGRES_MIGRATIONS, auto_migrate)?,
+    };
+
+    // Rules changed under an existing ledger: its hashes no longer hold
+    if let Some(recorded) = fetch_ledger_info(conn)? {
+        if recorded.protocol_version != PROTOCOL_VERSION && fetch_last_block(conn)?.is_some() {
+            return Err(ArtifactError::Schema(format!(
+                "Database was indexed with protocol version {}, this build speaks version {}; reindex into a new database",
+                recorded.protocol_version, PROTOCOL_VERSION
+            )));
+        }
+    }
+
+    upsert_ledger_info(conn, &schema_version, &PROTOCOL_VERSION)
+}
+
+/// Apply pending migrations, returning the resulting schema version
+fn migrate<C, DB>(
+    conn: &mut C,
+    migrations: EmbeddedMigrations,
+    auto_migrate: bool,
+) -> Result<String, ArtifactError>
+where
+    DB: Backend,
+    C: MigrationHarness<DB>,
+    EmbeddedMigrations: MigrationSource<DB>,
+{
+    let known = MigrationSource::<DB>::migrations(&migrations)
+        .map_err(schema_error)?
+        .iter()
+        .map(|m| m.name().version().to_string())
+        .collect::<Vec<_>>();
+    let latest = known.iter().max().cloned().unwrap_or_default();
+
+    // Written by a newer build
+    let applied = conn.applied_migrations().map_err(schema_error)?;
+    if let Some(unknown) = applied
+        .iter()
+        .map(|v| v.to_string())
+        .find(|v| !known.contains(v))
+    {
+        return Err(ArtifactError::Schema(format!(
+            "Database schema {} is newer than this build supports ({})",
+            unknown, latest
+        )));
+    }
+
+    let pending = known
+        .iter()
+        .filter(|v| !applied.iter().any(|a| a.to_string() == **v))
+        .count();
+    if pending > 0 {
+        if !auto_migrate {
+            return Err(ArtifactError::Schema(format!(
+                "{} pending migrations up to {}; enable ARTIFACT_AUTO_MIGRATE or migrate manually",
+                pending, latest
+            )));
+        }
+
+        conn.run_pending_migrations(migrations)
+            .map_err(schema_error)?;
+    }
+
+    Ok(latest)
+}
+
+fn schema_error(e: Box<dyn std::error::Error + Send + Sync>) -> ArtifactError {
+    ArtifactError::Schema(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::with_backend;
+    use crate::models::block::create_block;
+    use crate::models::ledger_info::fetch_ledger_info;
+    use diesel::prelude::*;
+
+    fn fresh_connection() -> DbConnection {
+        DbConnection::establish(":memory:").unwrap()
+    }
+
+    #[test]
+    fn prepare_migrates_and_records_versions() {
+        let mut conn = fresh_connection();
+
+        prepare(&mut conn, true).unwrap();
+
+        // Case: Versions Recorded
+        let info = fetch_ledger_info(&mut conn).unwrap().unwrap();
+        assert_eq!(info.schema_version, "20221010000021");
+        assert_eq!(info.protocol_version, PROTOCOL_VERSION);
+        // Case: Reopen Is A No-Op
+        prepare(&mut conn, false).unwrap();
+    }
+
+    #[test]
+    fn prepare_refuses_pending_migrations() {
+        let mut conn = fresh_connection();
+
+        // Case: Auto Migrate Disabled
+        assert!(matches!(
+            prepare(&mut conn, false),
+            Err(ArtifactError::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn prepare_rejects_newer_schema() {
+        let mut conn = fresh_connection();
+        prepare(&mut conn, true).unwrap();
+
+        with_backend!(&mut conn, |conn| {
+            diesel::sql_query(
+                "INSERT INTO __diesel_schema_migrations (version) VALUES ('2099-01-01-000001')",
+            )
+            .execute(conn)
+        })
+        .unwrap();
+
+        // Case: Unknown Migration
+        let error = prepare(&mut conn, true).unwrap_err();
+        assert!(error.to_string().contains("2099-01-01-000001 is newer"));
+    }
+
+    #[test]
+    fn prepare_rejects_other_protocol_versions() {
+        let mut conn = fresh_connection();
+        prepare(&mut conn, true).unwrap();
+        upsert_ledger_info(&mut conn, "20221010000021", &(PROTOCOL_VERSION + 1)).unwrap();
+
+        // Case: Nothing Indexed Yet
+        prepare(&mut conn, true).unwrap();
+        assert_eq!(
+            fetch_ledger_info(&mut conn)
+                .unwrap()
+                .unwrap()
+                .protocol_version,
+            PROTOCOL_VERSION
+        );
+
+        upsert_ledger_info(&mut conn, "20221010000021", &(PROTOCOL_VERSION + 1)).unwrap();
+        create_block(&mut conn, &0, "hash", "prev", "ledger").unwrap();
+
+        // Case: Indexed Ledger
+        let error = prepare(&mut conn, true).unwrap_err();
+        assert_eq!(error.code(), 103);
+        assert!(error.to_string().contains("reindex"));
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn backends_share_migration_versions() {
+        let sqlite = MigrationSource::<diesel::sqlite::Sqlite>::migrations(&SQLITE_MIGRATIONS).unwrap();
+        let postgres = MigrationSource::<diesel::pg::Pg>::migrations(&POSTGRES_MIGRATIONS).unwrap();
+
+        // Case: Same Step Under The Same Version
+        assert_eq!(
+            sqlite.iter().map(|m| m.name().to_string()).collect::<Vec<_>>(),
+            postgres.iter().map(|m| m.name().to_string()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,13 +1,20 @@
-use crate::schema::addresses;
+use crate::database::{with_backend, DbConnection};
+use crate::error::{ArtifactError, Reason};
+use crate::models::message::{Event, Origin};
+use crate::schema::{address_events, addresses};
+use crate::store::LedgerStore;
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::Network;
 use diesel::prelude::*;
 use validator::Validate;
 
-#[derive(Queryable, Validate)]
+#[derive(Queryable, Validate, Clone)]
 #[diesel(primary_key(address))]
 pub struct Address {
     pub address: String,
     #[validate(range(min = 0, max = 3))]
     pub flags: i32,
+    pub unlock_height: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -17,31 +24,427 @@ pub struct NewAddress<'a> {
     pub flags: &'a i32,
 }
 
+#[derive(Queryable, Clone)]
+#[diesel(belongs_to(Address, foreign_key = address))]
+pub struct AddressEvent {
+    pub id: i32,
+    pub address: String,
+    pub event: String,
+    pub flags: i32,
+    pub unlock_height: Option<i32>,
+    pub block_height: i32,
+    pub txid: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = address_events)]
+pub struct NewAddressEvent<'a> {
+    pub address: &'a str,
+    pub event: &'a str,
+    pub flags: &'a i32,
+    pub unlock_height: Option<&'a i32>,
+    pub block_height: &'a i32,
+    pub txid: Option<&'a str>,
+}
+
 bitflags! {
     #[derive(Default)]
-    struct Flags: i32 {
+    pub struct Flags: i32 {
         const LOCKED = 0b00000001;
         const MEMOFIELD = 0b00000010;
     }
 }
 
+/// Blocks between an unlock message and outbound transfers resuming
+pub const UNLOCK_DELAY: i32 = 144;
+
+/// Validation (a `network` address string)
+pub fn validate_address(address: &str, network: Network) -> Result<(), ArtifactError> {
+    match address.parse::<bitcoin::Address<NetworkUnchecked>>() {
+        Ok(parsed) if parsed.is_valid_for_network(network) => Ok(()),
+        _ => Err(ArtifactError::validation(
+            Reason::InvalidAddress,
+            format!("{} is not a {} address", address, network),
+        )),
+    }
+}
+
+/// Parsing (lock the source address)
+pub fn parse_lock<S: LedgerStore>(store: &mut S, origin: &Origin, source: &str) -> Result<(), ArtifactError> {
+    let current = store.fetch_address(source)?;
+
+    // A lock cancels any pending unlock
+    if let Some(Address { flags, unlock_height: None, .. }) = current {
+        if Flags::from_bits_truncate(flags).contains(Flags::LOCKED) {
+            return Err(ArtifactError::rejected(
+                Reason::AddressLocked,
+                format!("{} is already locked", source),
+            ));
+        }
+    }
+
+    store.transaction(|store| {
+        store.ensure_address(source)?;
+
+        let current = Flags::from_bits_truncate(store.fetch_address(source)?.map_or(0, |a| a.flags));
+        let locked = current | Flags::LOCKED;
+        store.update_flags(source, locked, None)?;
+
+        store.create_address_event(&NewAddressEvent {
+            address: source,
+            event: Event::Lock.as_str(),
+            flags: &locked.bits(),
+            unlock_height: None,
+            block_height: &origin.block_height,
+            txid: Some(origin.txid),
+        })
+    })
+}
+
+/// Parsing (schedule unlocking the source address)
+pub fn parse_unlock<S: LedgerStore>(store: &mut S, origin: &Origin, source: &str) -> Result<(), ArtifactError> {
+    let current = store
+        .fetch_address(source)?
+        .filter(|a| Flags::from_bits_truncate(a.flags).contains(Flags::LOCKED))
+        .ok_or_else(|| ArtifactError::rejected(Reason::AddressNotLocked, format!("{} is not locked", source)))?;
+
+    if let Some(unlock_height) = current.unlock_height {
+        return Err(ArtifactError::rejected(
+            Reason::UnlockPending,
+            format!("{} unlocks at height {}", source, unlock_height),
+        ));
+    }
+
+    let unlock_height = origin.block_height + UNLOCK_DELAY;
+
+    store.transaction(|store| {
+        store.update_flags(source, Flags::from_bits_truncate(current.flags), Some(unlock_height))?;
+
+        store.create_address_event(&NewAddressEvent {
+            address: source,
+            event: Event::Unlock.as_str(),
+            flags: &current.flags,
+            unlock_height: Some(&unlock_height),
+            block_height: &origin.block_height,
+            txid: Some(origin.txid),
+        })
+    })
+}
+
+/// Parsing (set the source address's flags)
+pub fn parse_options<S: LedgerStore>(
+    store: &mut S,
+    origin: &Origin,
+    source: &str,
+    destination: &str,
+    requested_flags: i32,
+) -> Result<(), ArtifactError> {
+    // Validate Subject
+    if source != destination {
+        return Err(ArtifactError::rejected(
+            Reason::InvalidAddress,
+            format!("{} can only change its own options", source),
+        ));
+    }
+
+    let requested = Flags::from_bits(requested_flags).ok_or_else(|| {
+        ArtifactError::validation(Reason::InvalidFlags, format!("Unknown flag bits in {:#010b}", requested_flags))
+    })?;
+    let current_flags = Flags::from_bits_truncate(store.fetch_address(source)?.map_or(0, |a| a.flags));
+
+    // LOCKED only clears through a delayed unlock
+    if current_flags.contains(Flags::LOCKED) && !requested.contains(Flags::LOCKED) {
+        return Err(ArtifactError::rejected(
+            Reason::AddressLocked,
+            format!("{} must be unlocked with an unlock message", source),
+        ));
+    }
+
+    // Requesting LOCKED locks again, cancelling any pending unlock like a
+    // lock message
+    store.transaction(|store| {
+        store.ensure_address(source)?;
+        store.update_flags(source, requested, None)?;
+
+        store.create_address_event(&NewAddressEvent {
+            address: source,
+            event: Event::Options.as_str(),
+            flags: &requested.bits(),
+            unlock_height: None,
+            block_height: &origin.block_height,
+            txid: Some(origin.txid),
+        })
+    })
+}
+
+/// Validation (locked addresses cannot move anything out)
+pub fn validate_unlocked<S: LedgerStore>(store: &mut S, address: &str) -> Result<(), ArtifactError> {
+    match store.fetch_address(address)? {
+        Some(current) if Flags::from_bits_truncate(current.flags).contains(Flags::LOCKED) => {
+            Err(ArtifactError::rejected(Reason::AddressLocked, format!("{} is locked", address)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Clear LOCKED on addresses whose unlock delay ends at or before
+/// `block_height`, journaled at the height each fell due
+pub fn release_unlocks(conn: &mut DbConnection, block_height: i32) -> Result<(), ArtifactError> {
+    use crate::models::address::addresses::dsl::*;
+
+    let due = with_backend!(conn, |conn| {
+        addresses
+            .filter(unlock_height.le(block_height))
+            .order(address.asc())
+            .load::<Address>(conn)
+    })?;
+
+    for due_address in due {
+        let unlocked = Flags::from_bits_truncate(due_address.flags) - Flags::LOCKED;
+        update_flags(conn, &due_address.address, unlocked, None)?;
+
+        create_address_event(
+            conn,
+            &NewAddressEvent {
+                address: &due_address.address,
+                event: Event::Release.as_str(),
+                flags: &unlocked.bits(),
+                unlock_height: None,
+                block_height: &due_address.unlock_height.unwrap_or(block_height),
+                txid: None,
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Clear every address option (before re-indexing from the start)
+pub fn reset_address_flags(conn: &mut DbConnection) -> Result<(), ArtifactError> {
+    use crate::models::address::addresses::dsl::*;
+
+    with_backend!(conn, |conn| {
+        diesel::update(addresses)
+            .set((flags.eq(0), unlock_height.eq(None::<i32>)))
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Update DB
+pub fn update_flags(
+    conn: &mut DbConnection,
+    address_name: &str,
+    new_flags: Flags,
+    new_unlock_height: Option<i32>,
+) -> Result<(), ArtifactError> {
+    use crate::models::address::addresses::dsl::*;
+
+    with_backend!(conn, |conn| {
+        diesel::update(addresses.find(address_name))
+            .set((flags.eq(new_flags.bits()), unlock_height.eq(new_unlock_height)))
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Insert DB
+pub fn create_address_event(conn: &mut DbConnection, new_event: &NewAddressEvent) -> Result<(), ArtifactError> {
+    with_backend!(conn, |conn| {
+        diesel::insert_into(address_events::table)
+            .values(new_event)
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_address_events(conn: &mut DbConnection, address_name: &str) -> Result<Vec<AddressEvent>, ArtifactError> {
+    use crate::models::address::address_events::dsl::*;
+
+    with_backend!(conn, |conn| {
+        address_events
+            .filter(address.eq(address_name))
+            .order(id.asc())
+            .load::<AddressEvent>(conn)
+            .map_err(ArtifactError::from)
+    })
+}
+
 /// Save to DB
-pub fn create_address(conn: &mut SqliteConnection, address: &str, flags: &i32) {
+pub fn create_address(conn: &mut DbConnection, address: &str, flags: &i32) -> Result<(), ArtifactError> {
     let new_address = NewAddress { address, flags };
 
-    diesel::insert_into(addresses::table)
-        .values(&new_address)
-        .execute(conn)
-        .expect("Error saving new address");
+    with_backend!(conn, |conn| {
+        diesel::insert_into(addresses::table)
+            .values(&new_address)
+            .execute(conn)
+    })?;
+
+    Ok(())
 }
 
-/// Save to DB
-pub fn update_address(conn: &mut SqliteConnection, address: &str, new_flags: &i32) {
-    use crate::models::address::addresses::dsl::addresses;
-    use crate::models::address::addresses::dsl::flags;
-
-    diesel::update(addresses.find(address))
-        .set(flags.eq(new_flags))
-        .execute(conn)
-        .expect("Error updating address");
+/// Insert DB (unless known)
+pub fn ensure_address(conn: &mut DbConnection, address: &str) -> Result<(), ArtifactError> {
+    let new_address = NewAddress { address, flags: &0 };
+
+    with_backend!(conn, |conn| {
+        diesel::insert_into(addresses::table)
+            .values(&new_address)
+            .on_conflict_do_nothing()
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_address(conn: &mut DbConnection, address_name: &str) -> Result<Option<Address>, ArtifactError> {
+    use crate::models::address::addresses::dsl::*;
+
+    with_backend!(conn, |conn| {
+        addresses.find(address_name).first::<Address>(conn).optional().map_err(ArtifactError::from)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::establish_test_connection;
+
+    fn origin(block_height: i32) -> Origin<'static> {
+        Origin {
+            block_height,
+            tx_index: 0,
+            txid: "txid",
+            message_id: Some(1),
+        }
+    }
+
+    #[test]
+    fn validate_address_checks_network() {
+        let mainnet = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+        // Case: Matching Network
+        assert!(validate_address(mainnet, Network::Bitcoin).is_ok());
+        // Case: Other Network
+        assert!(validate_address(mainnet, Network::Regtest).is_err());
+        // Case: Not An Address
+        assert_eq!(
+            validate_address("alice", Network::Bitcoin).unwrap_err().reason(),
+            Some(Reason::InvalidAddress)
+        );
+    }
+
+    #[test]
+    fn test_lock_and_delayed_unlock() {
+        let mut conn = establish_test_connection();
+        create_address(&mut conn, "cold", &Flags::MEMOFIELD.bits()).unwrap();
+
+        // Case: Unlock While Unlocked
+        assert!(parse_unlock(&mut conn, &origin(10), "cold").is_err());
+
+        parse_lock(&mut conn, &origin(5), "cold").unwrap();
+
+        // Case: Locked
+        assert!(validate_unlocked(&mut conn, "cold").is_err());
+        assert!(parse_lock(&mut conn, &origin(6), "cold").is_err());
+
+        parse_unlock(&mut conn, &origin(10), "cold").unwrap();
+
+        // Case: Unlock Pending
+        assert!(parse_unlock(&mut conn, &origin(11), "cold").is_err());
+        release_unlocks(&mut conn, 10 + UNLOCK_DELAY - 1).unwrap();
+        assert!(validate_unlocked(&mut conn, "cold").is_err());
+        // Case: Unlocked After Delay
+        release_unlocks(&mut conn, 10 + UNLOCK_DELAY).unwrap();
+        assert!(validate_unlocked(&mut conn, "cold").is_ok());
+        // Case: Other Flags Kept
+        assert_eq!(fetch_address(&mut conn, "cold").unwrap().unwrap().flags, Flags::MEMOFIELD.bits());
+        // Case: Changes Journaled
+        let events = fetch_address_events(&mut conn, "cold").unwrap();
+        let journaled = events
+            .iter()
+            .map(|e| (e.event.as_str(), e.flags, e.unlock_height, e.block_height))
+            .collect::<Vec<_>>();
+        let (memo, locked) = (Flags::MEMOFIELD.bits(), (Flags::MEMOFIELD | Flags::LOCKED).bits());
+        assert_eq!(
+            journaled,
+            vec![
+                ("lock", locked, None, 5),
+                ("unlock", locked, Some(10 + UNLOCK_DELAY), 10),
+                ("release", memo, None, 10 + UNLOCK_DELAY),
+            ]
+        );
+        assert_eq!(events[2].txid, None);
+    }
+
+    #[test]
+    fn test_options_set_own_flags() {
+        let mut conn = establish_test_connection();
+        let (memo, locked) = (Flags::MEMOFIELD.bits(), Flags::LOCKED.bits());
+
+        // Case: Someone Else's Address
+        assert!(parse_options(&mut conn, &origin(20), "mallory", "exchange", memo).is_err());
+        assert_eq!(fetch_address(&mut conn, "exchange").unwrap().map(|a| a.flags), None);
+
+        parse_options(&mut conn, &origin(20), "exchange", "exchange", memo).unwrap();
+
+        // Case: Memo Required
+        assert_eq!(fetch_address(&mut conn, "exchange").unwrap().unwrap().flags, memo);
+        // Case: Unknown Flags
+        assert!(parse_options(&mut conn, &origin(20), "exchange", "exchange", 0b0100).is_err());
+
+        parse_options(&mut conn, &origin(20), "exchange", "exchange", memo | locked).unwrap();
+
+        // Case: Locked Through Options
+        assert!(validate_unlocked(&mut conn, "exchange").is_err());
+        // Case: Cannot Clear LOCKED Directly
+        assert!(parse_options(&mut conn, &origin(20), "exchange", "exchange", memo).is_err());
+        // Case: Other Flags Still Change
+        parse_options(&mut conn, &origin(20), "exchange", "exchange", locked).unwrap();
+        assert_eq!(fetch_address(&mut conn, "exchange").unwrap().unwrap().flags, locked);
+        // Case: Accepted Changes Journaled
+        let journaled = fetch_address_events(&mut conn, "exchange").unwrap();
+        assert_eq!(
+            journaled.iter().map(|e| (e.event.as_str(), e.flags)).collect::<Vec<_>>(),
+            vec![("options", memo), ("options", memo | locked), ("options", locked)]
+        );
+    }
+
+    #[test]
+    fn test_lock_cancels_pending_unlock() {
+        let mut conn = establish_test_connection();
+
+        parse_lock(&mut conn, &origin(5), "cold").unwrap();
+        parse_unlock(&mut conn, &origin(10), "cold").unwrap();
+        parse_lock(&mut conn, &origin(11), "cold").unwrap();
+        release_unlocks(&mut conn, 10 + UNLOCK_DELAY).unwrap();
+
+        // Case: Still Locked
+        assert!(validate_unlocked(&mut conn, "cold").is_err());
+        assert_eq!(fetch_address(&mut conn, "cold").unwrap().unwrap().unlock_height, None);
+    }
+
+    #[test]
+    fn test_options_cancel_pending_unlock() {
+        let mut conn = establish_test_connection();
+        let (memo, locked) = (Flags::MEMOFIELD.bits(), Flags::LOCKED.bits());
+
+        parse_lock(&mut conn, &origin(5), "cold").unwrap();
+        parse_unlock(&mut conn, &origin(10), "cold").unwrap();
+        parse_options(&mut conn, &origin(11), "cold", "cold", memo | locked).unwrap();
+        release_unlocks(&mut conn, 10 + UNLOCK_DELAY).unwrap();
+
+        // Case: Still Locked, Like A Lock Message
+        let cold = fetch_address(&mut conn, "cold").unwrap().unwrap();
+        assert!(validate_unlocked(&mut conn, "cold").is_err());
+        assert_eq!((cold.flags, cold.unlock_height), (memo | locked, None));
+        // Case: Journaled Without The Unlock
+        let journaled = fetch_address_events(&mut conn, "cold").unwrap();
+        assert_eq!(journaled.last().map(|e| (e.event.as_str(), e.unlock_height)), Some(("options", None)));
+    }
 }
@@ -1,5 +1,8 @@
-use crate::schema::balances;
+use crate::database::{with_backend, DbConnection};
+use crate::error::{ArtifactError, Reason};
+use crate::schema::{balances, credits, debits, tokens};
 use diesel::prelude::*;
+use std::collections::BTreeMap;
 use validator::Validate;
 
 #[derive(Queryable, Validate)]
@@ -8,8 +11,8 @@ use validator::Validate;
 pub struct Balance {
     pub address: String,
     pub token: String,
-    #[validate(range(min = 0, max = 10000))]
-    pub quantity: i32,
+    #[validate(range(min = 0))]
+    pub quantity: i64,
 }
 
 #[derive(Insertable)]
@@ -17,19 +20,165 @@ pub struct Balance {
 pub struct NewBalance<'a> {
     pub address: &'a str,
     pub token: &'a str,
-    pub quantity: &'a i32,
+    pub quantity: &'a i64,
 }
 
 /// Save to DB
-pub fn create_balance(conn: &mut SqliteConnection, address: &str, token: &str, quantity: &i32) {
+pub fn create_balance(conn: &mut DbConnection, address: &str, token: &str, quantity: &i64) -> Result<(), ArtifactError> {
     let new_balance = NewBalance {
         address,
         token,
         quantity,
     };
 
-    diesel::insert_into(balances::table)
-        .values(&new_balance)
-        .execute(conn)
-        .expect("Error saving new balance");
+    with_backend!(conn, |conn| {
+        diesel::insert_into(balances::table)
+            .values(&new_balance)
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Save to DB
+pub fn update_balance(conn: &mut DbConnection, address: &str, token: &str, new_quantity: &i64) -> Result<(), ArtifactError> {
+    use crate::models::balance::balances::dsl::balances;
+    use crate::models::balance::balances::dsl::quantity;
+
+    with_backend!(conn, |conn| {
+        diesel::update(balances.find((address, token)))
+            .set(quantity.eq(new_quantity))
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_quantity(conn: &mut DbConnection, address_name: &str, token_name: &str) -> Result<i64, ArtifactError> {
+    use crate::models::balance::balances::dsl::*;
+
+    with_backend!(conn, |conn| {
+        balances
+            .find((address_name, token_name))
+            .select(quantity)
+            .first::<i64>(conn)
+            .optional()
+            .map(|q| q.unwrap_or(0))
+            .map_err(ArtifactError::from)
+    })
+}
+
+/// Filter DB (every holder of `token_name`)
+pub fn fetch_token_balances(conn: &mut DbConnection, token_name: &str) -> Result<Vec<Balance>, ArtifactError> {
+    use crate::models::balance::balances::dsl::*;
+
+    with_backend!(conn, |conn| {
+        balances
+            .filter(token.eq(token_name))
+            .load::<Balance>(conn)
+            .map_err(ArtifactError::from)
+    })
+}
+
+/// Filter DB (every balance with its token's divisibility)
+pub fn fetch_balances_with_divisibility(conn: &mut DbConnection) -> Result<Vec<(Balance, i32)>, ArtifactError> {
+    with_backend!(conn, |conn| {
+        balances::table
+            .inner_join(tokens::table)
+            .order((balances::token.asc(), balances::address.asc()))
+            .select(((balances::address, balances::token, balances::quantity), tokens::divisibility))
+            .load::<(Balance, i32)>(conn)
+            .map_err(ArtifactError::from)
+    })
+}
+
+/// Upsert DB
+pub fn upsert_balance(conn: &mut DbConnection, address: &str, token: &str, new_quantity: &i64) -> Result<(), ArtifactError> {
+    let new_balance = NewBalance {
+        address,
+        token,
+        quantity: new_quantity,
+    };
+
+    with_backend!(conn, |conn| {
+        diesel::insert_into(balances::table)
+            .values(&new_balance)
+            .on_conflict((balances::address, balances::token))
+            .do_update()
+            .set(balances::quantity.eq(new_quantity))
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Journal Totals (credits minus debits)
+fn journal_totals(conn: &mut DbConnection) -> Result<BTreeMap<(String, String), i128>, ArtifactError> {
+    let mut totals = BTreeMap::new();
+
+    let credited = with_backend!(conn, |conn| {
+        credits::table
+            .select((credits::address, credits::token, credits::quantity))
+            .load::<(String, String, i64)>(conn)
+    })?;
+    for (address, token, quantity) in credited {
+        *totals.entry((address, token)).or_insert(0) += quantity as i128;
+    }
+
+    let debited = with_backend!(conn, |conn| {
+        debits::table
+            .select((debits::address, debits::token, debits::quantity))
+            .load::<(String, String, i64)>(conn)
+    })?;
+    for (address, token, quantity) in debited {
+        *totals.entry((address, token)).or_insert(0) -= quantity as i128;
+    }
+
+    Ok(totals)
+}
+
+/// Rebuild DB (from the credit and debit journals)
+pub fn rebuild_balances(conn: &mut DbConnection) -> Result<(), ArtifactError> {
+    conn.transaction(|conn| {
+        let totals = journal_totals(conn)?;
+
+        with_backend!(conn, |conn| {
+            diesel::delete(balances::table).execute(conn)
+        })?;
+        for ((address, token), total) in totals {
+            let quantity = i64::try_from(total).map_err(|_| {
+                ArtifactError::validation(Reason::InvalidQuantity, format!("{} {} journal is out of range", address, token))
+            })?;
+            upsert_balance(conn, &address, &token, &quantity)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Audit DB (address, token, stored, journaled) for every mismatch
+pub fn audit_balances(conn: &mut DbConnection) -> Result<Vec<(String, String, i64, i128)>, ArtifactError> {
+    let mut totals = journal_totals(conn)?;
+
+    let stored = with_backend!(conn, |conn| {
+        balances::table
+            .select((balances::address, balances::token, balances::quantity))
+            .load::<(String, String, i64)>(conn)
+    })?;
+
+    let mut mismatches = vec![];
+    for (address, token, quantity) in stored {
+        let journaled = totals.remove(&(address.clone(), token.clone())).unwrap_or(0);
+        if journaled != quantity as i128 {
+            mismatches.push((address, token, quantity, journaled));
+        }
+    }
+    for ((address, token), journaled) in totals {
+        if journaled != 0 {
+            mismatches.push((address, token, 0, journaled));
+        }
+    }
+
+    Ok(mismatches)
 }
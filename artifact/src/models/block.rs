@@ -0,0 +1,86 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::ArtifactError;
+use crate::schema::blocks;
+use diesel::prelude::*;
+
+#[derive(Queryable)]
+#[diesel(primary_key(height))]
+pub struct Block {
+    pub height: i32,
+    pub hash: String,
+    pub prev_hash: String,
+    pub ledger_hash: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = blocks)]
+pub struct NewBlock<'a> {
+    pub height: &'a i32,
+    pub hash: &'a str,
+    pub prev_hash: &'a str,
+    pub ledger_hash: &'a str,
+}
+
+/// Insert DB
+pub fn create_block(
+    conn: &mut DbConnection,
+    height: &i32,
+    hash: &str,
+    prev_hash: &str,
+    ledger_hash: &str,
+) -> Result<(), ArtifactError> {
+    let new_block = NewBlock {
+        height,
+        hash,
+        prev_hash,
+        ledger_hash,
+    };
+
+    with_backend!(conn, |conn| {
+        diesel::insert_into(blocks::table)
+            .values(&new_block)
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_block(conn: &mut DbConnection, block_height: i32) -> Result<Option<Block>, ArtifactError> {
+    use crate::models::block::blocks::dsl::*;
+
+    with_backend!(conn, |conn| {
+        blocks.find(block_height).first::<Block>(conn).optional().map_err(ArtifactError::from)
+    })
+}
+
+/// Filter DB (consensus hash after `block_height`)
+pub fn fetch_ledger_hash(conn: &mut DbConnection, block_height: i32) -> Result<Option<String>, ArtifactError> {
+    use crate::models::block::blocks::dsl::*;
+
+    with_backend!(conn, |conn| {
+        blocks.find(block_height).select(ledger_hash).first::<String>(conn).optional()
+            .map_err(ArtifactError::from)
+    })
+}
+
+/// Filter DB (last committed block)
+pub fn fetch_last_block(conn: &mut DbConnection) -> Result<Option<Block>, ArtifactError> {
+    use crate::models::block::blocks::dsl::*;
+
+    with_backend!(conn, |conn| {
+        blocks.order(height.desc()).first::<Block>(conn).optional().map_err(ArtifactError::from)
+    })
+}
+
+/// Delete DB (every block above `block_height`)
+pub fn delete_blocks_after(conn: &mut DbConnection, block_height: i32) -> Result<(), ArtifactError> {
+    use crate::models::block::blocks::dsl::*;
+
+    with_backend!(conn, |conn| {
+        diesel::delete(blocks.filter(height.gt(block_height)))
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
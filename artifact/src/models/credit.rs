@@ -1,15 +1,27 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::ArtifactError;
+use crate::models::message::{Event, Origin};
 use crate::schema::credits;
 use diesel::prelude::*;
 use validator::Validate;
 
-#[derive(Queryable, Validate)]
+#[derive(Queryable, Validate, Clone)]
+#[diesel(primary_key(id))]
 #[diesel(belongs_to(Address, foreign_key = address))]
 #[diesel(belongs_to(Token, foreign_key = token))]
+#[diesel(belongs_to(Message, foreign_key = message_id))]
 pub struct Credit {
+    pub id: i32,
     pub address: String,
     pub token: String,
-    #[validate(range(min = 0, max = 10000))]
-    pub quantity: i32,
+    #[validate(range(min = 0))]
+    pub quantity: i64,
+    pub block_height: i32,
+    pub tx_index: i32,
+    pub txid: String,
+    pub event: String,
+    pub message_id: Option<i32>,
+    pub memo: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -17,19 +29,55 @@ pub struct Credit {
 pub struct NewCredit<'a> {
     pub address: &'a str,
     pub token: &'a str,
-    pub quantity: &'a i32,
+    pub quantity: &'a i64,
+    pub block_height: &'a i32,
+    pub tx_index: &'a i32,
+    pub txid: &'a str,
+    pub event: &'a str,
+    pub message_id: Option<&'a i32>,
+    pub memo: Option<&'a str>,
 }
 
-/// Save to DB
-pub fn create_credit(conn: &mut SqliteConnection, address: &str, token: &str, quantity: &i32) {
+/// Insert DB
+pub fn create_credit(
+    conn: &mut DbConnection,
+    origin: &Origin,
+    event: Event,
+    address: &str,
+    token: &str,
+    quantity: &i64,
+    memo: Option<&str>,
+) -> Result<(), ArtifactError> {
     let new_credit = NewCredit {
         address,
         token,
         quantity,
+        block_height: &origin.block_height,
+        tx_index: &origin.tx_index,
+        txid: origin.txid,
+        event: event.as_str(),
+        message_id: origin.message_id.as_ref(),
+        memo,
     };
 
-    diesel::insert_into(credits::table)
-        .values(&new_credit)
-        .execute(conn)
-        .expect("Error saving new credit");
+    with_backend!(conn, |conn| {
+        diesel::insert_into(credits::table)
+            .values(&new_credit)
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_credits(conn: &mut DbConnection, address_name: &str) -> Result<Vec<Credit>, ArtifactError> {
+    use crate::models::credit::credits::dsl::*;
+
+    with_backend!(conn, |conn| {
+        credits
+            .filter(address.eq(address_name))
+            .order(id.asc())
+            .load::<Credit>(conn)
+            .map_err(ArtifactError::from)
+    })
 }
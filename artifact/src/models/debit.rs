@@ -1,15 +1,26 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::ArtifactError;
+use crate::models::message::{Event, Origin};
 use crate::schema::debits;
 use diesel::prelude::*;
 use validator::Validate;
 
-#[derive(Queryable, Validate)]
+#[derive(Queryable, Validate, Clone)]
+#[diesel(primary_key(id))]
 #[diesel(belongs_to(Address, foreign_key = address))]
 #[diesel(belongs_to(Token, foreign_key = token))]
+#[diesel(belongs_to(Message, foreign_key = message_id))]
 pub struct Debit {
+    pub id: i32,
     pub address: String,
     pub token: String,
-    #[validate(range(min = 0, max = 10000))]
-    pub quantity: i32,
+    #[validate(range(min = 0))]
+    pub quantity: i64,
+    pub block_height: i32,
+    pub tx_index: i32,
+    pub txid: String,
+    pub event: String,
+    pub message_id: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -17,19 +28,52 @@ pub struct Debit {
 pub struct NewDebit<'a> {
     pub address: &'a str,
     pub token: &'a str,
-    pub quantity: &'a i32,
+    pub quantity: &'a i64,
+    pub block_height: &'a i32,
+    pub tx_index: &'a i32,
+    pub txid: &'a str,
+    pub event: &'a str,
+    pub message_id: Option<&'a i32>,
 }
 
-/// Save to DB
-pub fn create_debit(conn: &mut SqliteConnection, address: &str, token: &str, quantity: &i32) {
+/// Insert DB
+pub fn create_debit(
+    conn: &mut DbConnection,
+    origin: &Origin,
+    event: Event,
+    address: &str,
+    token: &str,
+    quantity: &i64,
+) -> Result<(), ArtifactError> {
     let new_debit = NewDebit {
         address,
         token,
         quantity,
+        block_height: &origin.block_height,
+        tx_index: &origin.tx_index,
+        txid: origin.txid,
+        event: event.as_str(),
+        message_id: origin.message_id.as_ref(),
     };
 
-    diesel::insert_into(debits::table)
-        .values(&new_debit)
-        .execute(conn)
-        .expect("Error saving new debit");
+    with_backend!(conn, |conn| {
+        diesel::insert_into(debits::table)
+            .values(&new_debit)
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_debits(conn: &mut DbConnection, address_name: &str) -> Result<Vec<Debit>, ArtifactError> {
+    use crate::models::debit::debits::dsl::*;
+
+    with_backend!(conn, |conn| {
+        debits
+            .filter(address.eq(address_name))
+            .order(id.asc())
+            .load::<Debit>(conn)
+            .map_err(ArtifactError::from)
+    })
 }
@@ -0,0 +1,53 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::ArtifactError;
+use crate::schema::issuances;
+use diesel::prelude::*;
+
+#[derive(Queryable, Clone)]
+#[diesel(belongs_to(Token, foreign_key = token))]
+pub struct Issuance {
+    pub id: i32,
+    pub token: String,
+    pub issuer: String,
+    pub flags: i32,
+    pub block_height: i32,
+    pub txid: String,
+    pub quantity: i64,
+    pub divisibility: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = issuances)]
+pub struct NewIssuance<'a> {
+    pub token: &'a str,
+    pub issuer: &'a str,
+    pub flags: &'a i32,
+    pub block_height: &'a i32,
+    pub txid: &'a str,
+    pub quantity: &'a i64,
+    pub divisibility: &'a i32,
+}
+
+/// Insert DB
+pub fn create_issuance(conn: &mut DbConnection, new_issuance: &NewIssuance) -> Result<(), ArtifactError> {
+    with_backend!(conn, |conn| {
+        diesel::insert_into(issuances::table)
+            .values(new_issuance)
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_issuances(conn: &mut DbConnection, token_name: &str) -> Result<Vec<Issuance>, ArtifactError> {
+    use crate::models::issuance::issuances::dsl::*;
+
+    with_backend!(conn, |conn| {
+        issuances
+            .filter(token.eq(token_name))
+            .order(id.asc())
+            .load::<Issuance>(conn)
+            .map_err(ArtifactError::from)
+    })
+}
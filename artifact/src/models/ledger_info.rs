@@ -0,0 +1,100 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::ArtifactError;
+use crate::schema::ledger_info;
+use bitcoin::Network;
+use diesel::prelude::*;
+
+#[derive(Queryable)]
+#[diesel(primary_key(id))]
+pub struct LedgerInfo {
+    pub id: i32,
+    pub schema_version: String,
+    pub protocol_version: i32,
+    pub network: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = ledger_info)]
+pub struct NewLedgerInfo<'a> {
+    pub id: &'a i32,
+    pub schema_version: &'a str,
+    pub protocol_version: &'a i32,
+}
+
+/// Upsert DB (the single row)
+pub fn upsert_ledger_info(
+    conn: &mut DbConnection,
+    schema_version: &str,
+    protocol_version: &i32,
+) -> Result<(), ArtifactError> {
+    let new_info = NewLedgerInfo {
+        id: &1,
+        schema_version,
+        protocol_version,
+    };
+
+    with_backend!(conn, |conn| {
+        diesel::insert_into(ledger_info::table)
+            .values(&new_info)
+            .on_conflict(ledger_info::id)
+            .do_update()
+            .set((
+                ledger_info::schema_version.eq(schema_version),
+                ledger_info::protocol_version.eq(protocol_version),
+            ))
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_ledger_info(conn: &mut DbConnection) -> Result<Option<LedgerInfo>, ArtifactError> {
+    use crate::models::ledger_info::ledger_info::dsl::*;
+
+    with_backend!(conn, |conn| {
+        ledger_info
+            .find(1)
+            .first::<LedgerInfo>(conn)
+            .optional()
+            .map_err(ArtifactError::from)
+    })
+}
+
+/// Validation (the first sync records `network`, later ones must match it)
+pub fn check_network(conn: &mut DbConnection, network: Network) -> Result<(), ArtifactError> {
+    let name = network.to_string();
+
+    with_backend!(conn, |conn| {
+        diesel::update(ledger_info::table.find(1).filter(ledger_info::network.is_null()))
+            .set(ledger_info::network.eq(&name))
+            .execute(conn)
+    })?;
+
+    match fetch_ledger_info(conn)?.and_then(|info| info.network) {
+        Some(recorded) if recorded != name => Err(ArtifactError::Schema(format!(
+            "Database was indexed on {}, but BITCOIN_NETWORK is {}",
+            recorded, name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::establish_test_connection;
+
+    #[test]
+    fn check_network_records_the_first_network() {
+        let mut conn = establish_test_connection();
+
+        // Case: Recorded On First Sync
+        check_network(&mut conn, Network::Regtest).unwrap();
+        assert_eq!(fetch_ledger_info(&mut conn).unwrap().unwrap().network.as_deref(), Some("regtest"));
+        // Case: Same Network Reopened
+        check_network(&mut conn, Network::Regtest).unwrap();
+        // Case: Other Network
+        assert_eq!(check_network(&mut conn, Network::Bitcoin).unwrap_err().code(), 103);
+    }
+}
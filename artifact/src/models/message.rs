@@ -0,0 +1,92 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::ArtifactError;
+use crate::schema::messages;
+use diesel::prelude::*;
+
+#[derive(Queryable)]
+#[diesel(primary_key(id))]
+pub struct Message {
+    pub id: i32,
+    pub block_height: i32,
+    pub tx_index: i32,
+    pub txid: String,
+    pub source: String,
+    pub destination: Option<String>,
+    pub message_type: i32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = messages)]
+pub struct NewMessage<'a> {
+    pub block_height: &'a i32,
+    pub tx_index: &'a i32,
+    pub txid: &'a str,
+    pub source: &'a str,
+    pub destination: Option<&'a str>,
+    pub message_type: &'a i32,
+    pub data: &'a [u8],
+}
+
+/// Provenance of a ledger event (the genesis state has no message)
+#[derive(Clone, Copy, Debug)]
+pub struct Origin<'a> {
+    pub block_height: i32,
+    pub tx_index: i32,
+    pub txid: &'a str,
+    pub message_id: Option<i32>,
+}
+
+/// Journal Event Types
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Send,
+    Issuance,
+    Lock,
+    Unlock,
+    Release,
+    Options,
+    Genesis,
+}
+
+impl Event {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Event::Send => "send",
+            Event::Issuance => "issuance",
+            Event::Lock => "lock",
+            Event::Unlock => "unlock",
+            Event::Release => "release",
+            Event::Options => "options",
+            Event::Genesis => "genesis",
+        }
+    }
+}
+
+diesel::define_sql_function!(fn last_insert_rowid() -> Integer);
+
+/// Insert DB
+pub fn create_message(conn: &mut DbConnection, new_message: &NewMessage) -> Result<i32, ArtifactError> {
+    match conn {
+        DbConnection::Sqlite(conn) => {
+            diesel::insert_into(messages::table)
+                .values(new_message)
+                .execute(conn)?;
+
+            diesel::select(last_insert_rowid()).get_result::<i32>(conn).map_err(ArtifactError::from)
+        }
+        #[cfg(feature = "postgres")]
+        DbConnection::Postgres(conn) => diesel::insert_into(messages::table)
+            .values(new_message)
+            .returning(messages::id)
+            .get_result::<i32>(conn)
+            .map_err(ArtifactError::from),
+    }
+}
+
+/// Filter DB
+pub fn fetch_message(conn: &mut DbConnection, message_id: i32) -> Result<Message, ArtifactError> {
+    use crate::models::message::messages::dsl::*;
+
+    with_backend!(conn, |conn| messages.find(message_id).first::<Message>(conn)).map_err(ArtifactError::from)
+}
@@ -1,5 +1,12 @@
 pub mod address;
 pub mod balance;
+pub mod block;
 pub mod credit;
 pub mod debit;
+pub mod issuance;
+pub mod ledger_info;
+pub mod message;
+pub mod ownership;
+pub mod rejection;
+pub mod send;
 pub mod token;
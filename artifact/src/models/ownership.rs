@@ -0,0 +1,142 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::{ArtifactError, Reason};
+use crate::models::address::validate_unlocked;
+use crate::models::message::Origin;
+use crate::models::token::{generate_token, validate_id};
+use crate::schema::ownership_transfers;
+use crate::store::LedgerStore;
+use diesel::prelude::*;
+
+#[derive(Queryable, Clone)]
+#[diesel(belongs_to(Token, foreign_key = token))]
+pub struct OwnershipTransfer {
+    pub id: i32,
+    pub token: String,
+    pub source: String,
+    pub destination: String,
+    pub block_height: i32,
+    pub txid: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = ownership_transfers)]
+pub struct NewOwnershipTransfer<'a> {
+    pub token: &'a str,
+    pub source: &'a str,
+    pub destination: &'a str,
+    pub block_height: &'a i32,
+    pub txid: &'a str,
+}
+
+/// Parsing
+pub fn parse<S: LedgerStore>(
+    store: &mut S,
+    origin: &Origin,
+    source: &str,
+    destination: &str,
+    token_id: u64,
+) -> Result<(), ArtifactError> {
+    // Validate Token ID Used
+    validate_id(token_id)?;
+
+    // Generate Token Name ID
+    let token_name = generate_token(token_id);
+
+    // Validate Token Issued
+    let token = store.fetch_token(&token_name)?.ok_or_else(|| {
+        ArtifactError::rejected(Reason::UnknownToken, format!("{} has not been issued", token_name))
+    })?;
+
+    // Validate Owner
+    if token.owner.as_deref() != Some(source) {
+        return Err(ArtifactError::rejected(
+            Reason::InvalidOwner,
+            format!("{} is not owned by {}", token_name, source),
+        ));
+    }
+
+    // Validate Owner Unlocked
+    validate_unlocked(store, source)?;
+
+    store.transaction(|store| {
+        store.ensure_address(destination)?;
+        store.update_owner(&token_name, destination)?;
+
+        store.create_ownership_transfer(&NewOwnershipTransfer {
+            token: &token_name,
+            source,
+            destination,
+            block_height: &origin.block_height,
+            txid: origin.txid,
+        })
+    })
+}
+
+/// Insert DB
+pub fn create_ownership_transfer(
+    conn: &mut DbConnection,
+    new_transfer: &NewOwnershipTransfer,
+) -> Result<(), ArtifactError> {
+    with_backend!(conn, |conn| {
+        diesel::insert_into(ownership_transfers::table)
+            .values(new_transfer)
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_ownership_transfers(
+    conn: &mut DbConnection,
+    token_name: &str,
+) -> Result<Vec<OwnershipTransfer>, ArtifactError> {
+    use crate::models::ownership::ownership_transfers::dsl::*;
+
+    with_backend!(conn, |conn| {
+        ownership_transfers
+            .filter(token.eq(token_name))
+            .order(id.asc())
+            .load::<OwnershipTransfer>(conn)
+            .map_err(ArtifactError::from)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::establish_test_connection;
+    use crate::models::token::{self, fetch_token, generate_id};
+
+    const ORIGIN: Origin = Origin {
+        block_height: 5,
+        tx_index: 0,
+        txid: "transfer",
+        message_id: Some(1),
+    };
+
+    #[test]
+    fn parse_moves_ownership() {
+        let mut conn = establish_test_connection();
+        let token_id = generate_id("OWNED").unwrap();
+
+        token::parse(&mut conn, &ORIGIN, "alice", token_id, 0, 0, 0).unwrap();
+
+        // Case: Not The Owner
+        assert!(parse(&mut conn, &ORIGIN, "mallory", "mallory", token_id).is_err());
+        // Case: Unknown Token
+        assert!(parse(&mut conn, &ORIGIN, "alice", "bob", generate_id("NOPE").unwrap()).is_err());
+
+        parse(&mut conn, &ORIGIN, "alice", "bob", token_id).unwrap();
+
+        // Case: Owner Updated
+        assert_eq!(fetch_token(&mut conn, "OWNED").unwrap().unwrap().owner.as_deref(), Some("bob"));
+        // Case: Transfer Recorded
+        let transfers = fetch_ownership_transfers(&mut conn, "OWNED").unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].source, "alice");
+        assert_eq!(transfers[0].destination, "bob");
+        // Case: Previous Owner
+        assert!(parse(&mut conn, &ORIGIN, "alice", "alice", token_id).is_err());
+    }
+}
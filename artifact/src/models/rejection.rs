@@ -0,0 +1,82 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::ArtifactError;
+use crate::schema::rejected_messages;
+use diesel::prelude::*;
+
+#[derive(Queryable, Debug)]
+#[diesel(primary_key(id))]
+#[diesel(belongs_to(Message, foreign_key = message_id))]
+pub struct Rejection {
+    pub id: i32,
+    pub block_height: i32,
+    pub tx_index: i32,
+    pub txid: String,
+    pub source: Option<String>,
+    pub destination: Option<String>,
+    pub message_type: Option<i32>,
+    pub reason_code: i32,
+    pub reason: String,
+    pub message_id: Option<i32>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = rejected_messages)]
+pub struct NewRejection<'a> {
+    pub block_height: &'a i32,
+    pub tx_index: &'a i32,
+    pub txid: &'a str,
+    pub source: Option<&'a str>,
+    pub destination: Option<&'a str>,
+    pub message_type: Option<&'a i32>,
+    pub reason_code: &'a i32,
+    pub reason: &'a str,
+    pub message_id: Option<&'a i32>,
+}
+
+/// Insert DB
+pub fn create_rejection(conn: &mut DbConnection, new_rejection: &NewRejection) -> Result<(), ArtifactError> {
+    with_backend!(conn, |conn| {
+        diesel::insert_into(rejected_messages::table)
+            .values(new_rejection)
+            .execute(conn)
+    })?;
+
+    Ok(())
+}
+
+/// Filter DB
+pub fn fetch_rejections_by_txid(conn: &mut DbConnection, txid_name: &str) -> Result<Vec<Rejection>, ArtifactError> {
+    use crate::models::rejection::rejected_messages::dsl::*;
+
+    with_backend!(conn, |conn| {
+        rejected_messages
+            .filter(txid.eq(txid_name))
+            .order(id.asc())
+            .load::<Rejection>(conn)
+            .map_err(ArtifactError::from)
+    })
+}
+
+/// Filter DB (sent from or to `address_name`)
+pub fn fetch_rejections_by_address(conn: &mut DbConnection, address_name: &str) -> Result<Vec<Rejection>, ArtifactError> {
+    use crate::models::rejection::rejected_messages::dsl::*;
+
+    with_backend!(conn, |conn| {
+        rejected_messages
+            .filter(source.eq(address_name).or(destination.eq(address_name)))
+            .order((block_height.desc(), tx_index.desc()))
+            .load::<Rejection>(conn)
+            .map_err(ArtifactError::from)
+    })
+}
+
+/// Delete DB (above `block_height`, the heights being re-indexed)
+pub fn delete_rejections(conn: &mut DbConnection, block_height_after: i32) -> Result<(), ArtifactError> {
+    use crate::models::rejection::rejected_messages::dsl::*;
+
+    with_backend!(conn, |conn| {
+        diesel::delete(rejected_messages.filter(block_height.gt(block_height_after))).execute(conn)
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,357 @@
+use crate::error::{ArtifactError, Reason};
+use crate::models::address::{validate_unlocked, Flags};
+use crate::models::message::{Event, Origin};
+use crate::models::token::{generate_token, validate_id};
+use crate::store::LedgerStore;
+
+/// Parsing
+pub fn parse<S: LedgerStore>(
+    store: &mut S,
+    origin: &Origin,
+    source: &str,
+    destination: &str,
+    token_id: u64,
+    quantity: u64,
+    memo: Option<&str>,
+) -> Result<(), ArtifactError> {
+    // Validate Token ID Used
+    validate_id(token_id)?;
+
+    // Generate Token Name ID
+    let token_name = generate_token(token_id);
+
+    // Validate Quantity
+    let quantity = match i64::try_from(quantity) {
+        Ok(0) => {
+            return Err(ArtifactError::validation(
+                Reason::InvalidQuantity,
+                "Must be greater than zero",
+            ))
+        }
+        Ok(quantity) => quantity,
+        Err(_) => {
+            return Err(ArtifactError::validation(
+                Reason::InvalidQuantity,
+                "Exceeds maximum quantity",
+            ))
+        }
+    };
+
+    // Validate Token Issued
+    if store.fetch_token(&token_name)?.is_none() {
+        return Err(ArtifactError::rejected(
+            Reason::UnknownToken,
+            format!("{} has not been issued", token_name),
+        ));
+    }
+
+    // Validate Sender Unlocked
+    validate_unlocked(store, source)?;
+
+    // Validate Memo Required
+    let destination_flags = store.fetch_address(destination)?.map_or(Flags::empty(), |a| Flags::from_bits_truncate(a.flags));
+    if destination_flags.contains(Flags::MEMOFIELD) && memo.is_none() {
+        return Err(ArtifactError::rejected(
+            Reason::MissingMemo,
+            format!("{} requires a memo", destination),
+        ));
+    }
+
+    // Validate Balance
+    let source_balance = store.fetch_quantity(source, &token_name)?;
+    if source_balance < quantity {
+        return Err(ArtifactError::rejected(
+            Reason::InsufficientBalance,
+            format!("{} available, {} requested", source_balance, quantity),
+        ));
+    }
+
+    // Validate Receiver Balance
+    let destination_balance = store.fetch_quantity(destination, &token_name)?;
+    if source != destination && destination_balance.checked_add(quantity).is_none() {
+        return Err(ArtifactError::rejected(Reason::InvalidQuantity, "Exceeds maximum quantity"));
+    }
+
+    store.transaction(|store| {
+        store.ensure_address(destination)?;
+
+        store.create_debit(origin, Event::Send, source, &token_name, &quantity)?;
+        store.create_credit(origin, Event::Send, destination, &token_name, &quantity, memo)?;
+
+        // Re-read so a send to self nets out
+        let source_balance = store.fetch_quantity(source, &token_name)?;
+        store.upsert_balance(source, &token_name, &(source_balance - quantity))?;
+        let destination_balance = store.fetch_quantity(destination, &token_name)?;
+        store.upsert_balance(destination, &token_name, &(destination_balance + quantity))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DbConnection;
+    use crate::fixtures::establish_test_connection;
+    use crate::models::address::create_address;
+    use crate::models::balance::{audit_balances, create_balance, rebuild_balances};
+    use crate::models::balance::{fetch_quantity, upsert_balance};
+    use crate::models::credit::create_credit;
+    use crate::models::credit::fetch_credits;
+    use crate::models::message::{create_message, NewMessage};
+    use crate::models::token::{create_token, generate_id};
+
+    const ORIGIN: Origin = Origin {
+        block_height: 1,
+        tx_index: 0,
+        txid: "txid",
+        message_id: Some(1),
+    };
+
+    fn funded_connection() -> DbConnection {
+        let mut conn = establish_test_connection();
+        create_message(
+            &mut conn,
+            &NewMessage {
+                block_height: &1,
+                tx_index: &0,
+                txid: "txid",
+                source: "alice",
+                destination: Some("bob"),
+                message_type: &0,
+                data: &[],
+            },
+        )
+        .unwrap();
+
+        create_token(&mut conn, "SEND", &0, &0).unwrap();
+        create_address(&mut conn, "alice", &0).unwrap();
+        create_credit(&mut conn, &ORIGIN, Event::Send, "alice", "SEND", &100, None).unwrap();
+        create_balance(&mut conn, "alice", "SEND", &100).unwrap();
+
+        conn
+    }
+
+    #[test]
+    fn test_send_moves_balance() {
+        let mut conn = funded_connection();
+
+        parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("SEND").unwrap(),
+            40,
+            None,
+        )
+        .unwrap();
+
+        // Case: Debited
+        assert_eq!(fetch_quantity(&mut conn, "alice", "SEND").unwrap(), 60);
+        // Case: Credited
+        assert_eq!(fetch_quantity(&mut conn, "bob", "SEND").unwrap(), 40);
+    }
+
+    #[test]
+    fn test_send_journals_every_movement() {
+        let mut conn = funded_connection();
+
+        parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("SEND").unwrap(),
+            40,
+            None,
+        )
+        .unwrap();
+        parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("SEND").unwrap(),
+            10,
+            None,
+        )
+        .unwrap();
+
+        let credits = fetch_credits(&mut conn, "bob").unwrap();
+
+        // Case: Repeated Credits
+        assert_eq!(credits.len(), 2);
+        assert_eq!(credits[1].quantity, 10);
+        assert_eq!(credits[1].event, "send");
+        assert_eq!(credits[1].message_id, Some(1));
+        // Case: Bob Explained By Journal
+        assert_eq!(fetch_quantity(&mut conn, "bob", "SEND").unwrap(), 50);
+        assert!(audit_balances(&mut conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rebuild_balances_from_journal() {
+        let mut conn = funded_connection();
+
+        parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("SEND").unwrap(),
+            40,
+            None,
+        )
+        .unwrap();
+        upsert_balance(&mut conn, "bob", "SEND", &1).unwrap();
+        rebuild_balances(&mut conn).unwrap();
+
+        // Case: Restored From Journal
+        assert_eq!(fetch_quantity(&mut conn, "bob", "SEND").unwrap(), 40);
+    }
+
+    #[test]
+    fn test_send_rejects_invalid_sends() {
+        let mut conn = funded_connection();
+
+        // Case: Above i64
+        assert!(parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("SEND").unwrap(),
+            u64::MAX,
+            None
+        )
+        .is_err());
+        // Case: Overspend
+        assert!(parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("SEND").unwrap(),
+            101,
+            None
+        )
+        .is_err());
+        // Case: Zero Quantity
+        assert!(parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("SEND").unwrap(),
+            0,
+            None
+        )
+        .is_err());
+        // Case: Unknown Token
+        assert!(parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("NOPE").unwrap(),
+            1,
+            None
+        )
+        .is_err());
+        // Case: Nothing Moved
+        assert_eq!(fetch_quantity(&mut conn, "alice", "SEND").unwrap(), 100);
+        assert_eq!(fetch_quantity(&mut conn, "bob", "SEND").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_send_requires_memo_when_flagged() {
+        let mut conn = funded_connection();
+        create_address(&mut conn, "exchange", &Flags::MEMOFIELD.bits()).unwrap();
+
+        // Case: Missing Memo
+        assert_eq!(
+            parse(
+                &mut conn,
+                &ORIGIN,
+                "alice",
+                "exchange",
+                generate_id("SEND").unwrap(),
+                5,
+                None
+            )
+            .unwrap_err()
+            .reason(),
+            Some(Reason::MissingMemo)
+        );
+
+        parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "exchange",
+            generate_id("SEND").unwrap(),
+            5,
+            Some("user-42"),
+        )
+        .unwrap();
+        parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("SEND").unwrap(),
+            5,
+            None,
+        )
+        .unwrap();
+
+        // Case: Memo In History
+        let credits = fetch_credits(&mut conn, "exchange").unwrap();
+        assert_eq!(credits[0].memo.as_deref(), Some("user-42"));
+        // Case: Memo Optional Elsewhere
+        assert_eq!(fetch_credits(&mut conn, "bob").unwrap()[0].memo, None);
+    }
+
+    #[test]
+    fn test_send_rejects_locked_sender() {
+        let mut conn = funded_connection();
+        crate::models::address::parse_lock(&mut conn, &ORIGIN, "alice").unwrap();
+
+        // Case: Outbound Frozen
+        assert_eq!(
+            parse(
+                &mut conn,
+                &ORIGIN,
+                "alice",
+                "bob",
+                generate_id("SEND").unwrap(),
+                5,
+                None
+            )
+            .unwrap_err()
+            .reason(),
+            Some(Reason::AddressLocked)
+        );
+        assert_eq!(fetch_quantity(&mut conn, "alice", "SEND").unwrap(), 100);
+    }
+
+    #[test]
+    fn test_send_rolls_back_on_failure() {
+        let mut conn = funded_connection();
+        create_address(&mut conn, "bob", &0).unwrap();
+        create_balance(&mut conn, "bob", "SEND", &(i64::MAX - 10)).unwrap();
+
+        // Case: Receiver Over Limit
+        assert!(parse(
+            &mut conn,
+            &ORIGIN,
+            "alice",
+            "bob",
+            generate_id("SEND").unwrap(),
+            20,
+            None
+        )
+        .is_err());
+        // Case: Sender Untouched
+        assert_eq!(fetch_quantity(&mut conn, "alice", "SEND").unwrap(), 100);
+    }
+}
@@ -1,14 +1,29 @@
+use crate::database::{with_backend, DbConnection};
+use crate::error::{ArtifactError, Reason};
+use crate::idn;
+use crate::models::issuance::NewIssuance;
+use crate::models::message::{Event, Origin};
 use crate::schema::tokens;
+use crate::store::LedgerStore;
 use diesel::prelude::*;
 use validator::{Validate, ValidationError};
 
-#[derive(Queryable, Validate)]
+#[derive(Queryable, Validate, Clone)]
 #[diesel(primary_key(token))]
 pub struct Token {
     #[validate(custom = "validate_token")]
     pub token: String,
     #[validate(range(min = 0, max = 3))]
     pub flags: i32,
+    #[validate(range(min = 0, max = 8))]
+    pub divisibility: i32,
+    pub owner: Option<String>,
+    pub issuer: Option<String>,
+    pub issued_at_height: Option<i32>,
+    pub issuance_txid: Option<String>,
+    #[validate(range(min = 0))]
+    pub total_supply: i64,
+    pub parent: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -16,6 +31,13 @@ pub struct Token {
 pub struct NewToken<'a> {
     pub token: &'a str,
     pub flags: &'a i32,
+    pub divisibility: &'a i32,
+    pub owner: Option<&'a str>,
+    pub issuer: Option<&'a str>,
+    pub issued_at_height: Option<&'a i32>,
+    pub issuance_txid: Option<&'a str>,
+    pub total_supply: &'a i64,
+    pub parent: Option<&'a str>,
 }
 
 bitflags! {
@@ -32,98 +54,368 @@ pub static VALID_CHARACTERS: [char; 38] = [
     'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9',
 ];
 
+/// Maximum decimal places
+pub const MAX_DIVISIBILITY: u8 = 8;
+
 /// Parsing
-pub fn parse(conn: &mut SqliteConnection, token_id: u64, token_flags: i32) -> Result<(), String> {
+pub fn parse<S: LedgerStore>(
+    store: &mut S,
+    origin: &Origin,
+    issuer: &str,
+    token_id: u64,
+    quantity: u64,
+    divisibility: u8,
+    token_flags: i32,
+) -> Result<(), ArtifactError> {
     // Validate Token ID Used
-    validate_id(token_id).map_err(|e| e.to_string())?;
+    validate_id(token_id)?;
 
     // Generate Token Name ID
     let token_name = generate_token(token_id);
 
     // Validate the token name format
-    validate_token(&token_name).map_err(|e| e.to_string())?;
-
-    // TODO: Validate Owner
-    // TODO: Validate other properties
-
-    match token_exists(conn, &token_name) {
-        Ok(exists) => {
-            if exists {
-                // If the token exists, update it
-                update_token(conn, &token_name, &token_flags)
-                    .map_err(|e| e.to_string())
-            } else {
-                // If the token does not exist, create it
-                create_token(conn, &token_name, &token_flags)
-                    .map_err(|e| e.to_string())
-            }
-        },
-        Err(e) => Err(format!("Error checking token existence: {}", e)),
+    check_token(&token_name)?;
+
+    // Validate Quantity
+    let quantity = i64::try_from(quantity)
+        .map_err(|_| ArtifactError::validation(Reason::InvalidQuantity, "Exceeds maximum quantity"))?;
+
+    // Validate Divisibility
+    if divisibility > MAX_DIVISIBILITY {
+        return Err(ArtifactError::validation(
+            Reason::InvalidDivisibility,
+            format!("Maximum is {}", MAX_DIVISIBILITY),
+        ));
+    }
+    let divisibility = divisibility as i32;
+
+    let existing = store.fetch_token(&token_name)?;
+
+    if let Some(existing) = &existing {
+        // Validate Not Reserved
+        if existing.owner.is_none() {
+            return Err(ArtifactError::rejected(
+                Reason::ReservedToken,
+                format!("{} is reserved", token_name),
+            ));
+        }
+
+        // Validate Owner
+        if existing.owner.as_deref() != Some(issuer) {
+            return Err(ArtifactError::rejected(
+                Reason::InvalidOwner,
+                format!("{} is not owned by {}", token_name, issuer),
+            ));
+        }
+
+        // Validate Flags
+        validate_flags(&token_name, existing.flags, token_flags)?;
+
+        // Divisibility is fixed by the first issuance
+        if existing.divisibility != divisibility {
+            return Err(ArtifactError::rejected(
+                Reason::InvalidDivisibility,
+                format!("{} was issued with {} decimals", token_name, existing.divisibility),
+            ));
+        }
+    }
+
+    // Validate Parent Namespace (first issuance of a subtoken)
+    let parent = match token_name.split_once('.') {
+        Some((parent, _)) if existing.is_none() => Some(validate_parent(store, parent, issuer)?),
+        _ => None,
+    };
+
+    // Validate Total Supply
+    let total_supply = existing
+        .as_ref()
+        .map_or(0, |t| t.total_supply)
+        .checked_add(quantity)
+        .ok_or_else(|| ArtifactError::rejected(Reason::InvalidQuantity, "Exceeds maximum supply"))?;
+
+    // Validate Issuer Balance
+    let issuer_balance = store
+        .fetch_quantity(issuer, &token_name)?
+        .checked_add(quantity)
+        .ok_or_else(|| ArtifactError::rejected(Reason::InvalidQuantity, "Exceeds maximum quantity"))?;
+
+    store.transaction(|store| {
+        if existing.is_some() {
+            // If the token exists, update it
+            store.update_token(&token_name, &token_flags)?;
+            store.update_supply(&token_name, &total_supply)?;
+        } else {
+            // If the token does not exist, create it owned by its issuer
+            store.create_token(&NewToken {
+                token: &token_name,
+                flags: &token_flags,
+                divisibility: &divisibility,
+                owner: Some(issuer),
+                issuer: Some(issuer),
+                issued_at_height: Some(&origin.block_height),
+                issuance_txid: Some(origin.txid),
+                total_supply: &total_supply,
+                parent: parent.as_deref(),
+            })?;
+        }
+
+        // Credit the issued supply to the issuer
+        if quantity > 0 {
+            store.ensure_address(issuer)?;
+            store.create_credit(origin, Event::Issuance, issuer, &token_name, &quantity, None)?;
+            store.upsert_balance(issuer, &token_name, &issuer_balance)?;
+        }
+
+        // Record who issued it, and where
+        store.create_issuance(&NewIssuance {
+            token: &token_name,
+            issuer,
+            flags: &token_flags,
+            block_height: &origin.block_height,
+            txid: origin.txid,
+            quantity: &quantity,
+            divisibility: &divisibility,
+        })
+    })
+}
+
+/// Validation (locked tokens are final, flags can only be added)
+pub fn validate_flags(token_name: &str, current: i32, requested: i32) -> Result<(), ArtifactError> {
+    let current = Flags::from_bits_truncate(current);
+    let requested = Flags::from_bits_truncate(requested);
+
+    if current.contains(Flags::LOCKED) {
+        return Err(ArtifactError::rejected(
+            Reason::TokenLocked,
+            format!("{} is locked, its supply and flags are final", token_name),
+        ));
+    }
+    if !requested.contains(current) {
+        return Err(ArtifactError::rejected(
+            Reason::InvalidFlags,
+            format!("{} cannot clear {:?}", token_name, current.difference(requested)),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validation (subtokens need a NAMESPACE parent owned by the issuer)
+fn validate_parent<S: LedgerStore>(store: &mut S, parent: &str, issuer: &str) -> Result<String, ArtifactError> {
+    let parent_token = store.fetch_token(parent)?.ok_or_else(|| {
+        ArtifactError::rejected(Reason::UnknownToken, format!("Parent {} has not been issued", parent))
+    })?;
+
+    if parent_token.owner.is_none() {
+        return Err(ArtifactError::rejected(
+            Reason::ReservedToken,
+            format!("{} is reserved", parent),
+        ));
+    }
+    if !Flags::from_bits_truncate(parent_token.flags).contains(Flags::NAMESPACE) {
+        return Err(ArtifactError::rejected(
+            Reason::InvalidNamespace,
+            format!("{} does not allow subtokens", parent),
+        ));
     }
+    if parent_token.owner.as_deref() != Some(issuer) {
+        return Err(ArtifactError::rejected(
+            Reason::InvalidOwner,
+            format!("{} is not owned by {}", parent, issuer),
+        ));
+    }
+
+    Ok(parent_token.token)
+}
+
+/// Formatting (base units to decimal string)
+pub fn format_quantity(quantity: u64, divisibility: u8) -> String {
+    if divisibility == 0 {
+        return quantity.to_string();
+    }
+
+    let scale = 10u64.pow(divisibility as u32);
+    format!(
+        "{}.{:0width$}",
+        quantity / scale,
+        quantity % scale,
+        width = divisibility as usize
+    )
+}
+
+/// Parsing (decimal string to base units)
+pub fn parse_quantity(quantity: &str, divisibility: u8) -> Result<u64, ArtifactError> {
+    let (whole, fraction) = quantity.split_once('.').unwrap_or((quantity, ""));
+
+    if whole.is_empty() || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return Err(ArtifactError::validation(
+            Reason::InvalidQuantity,
+            format!("'{}' is not a decimal number", quantity),
+        ));
+    }
+    if fraction.len() > divisibility as usize {
+        return Err(ArtifactError::validation(
+            Reason::InvalidQuantity,
+            format!("At most {} decimal places", divisibility),
+        ));
+    }
+
+    let scale = 10u64.pow(divisibility as u32);
+    let padded = format!("{:0<width$}", fraction, width = divisibility as usize);
+    let overflow = || ArtifactError::validation(Reason::InvalidQuantity, "Exceeds maximum quantity");
+
+    whole
+        .parse::<u64>()
+        .map_err(|_| overflow())?
+        .checked_mul(scale)
+        .and_then(|q| q.checked_add(padded.parse::<u64>().unwrap_or(0)))
+        .filter(|q| *q <= i64::MAX as u64)
+        .ok_or_else(overflow)
 }
 
 /// Insert DB
-fn create_token(conn: &mut SqliteConnection, token: &str, flags: &i32) -> Result<(), diesel::result::Error> {
-    let new_token = NewToken { token, flags };
+pub fn create_token(conn: &mut DbConnection, token: &str, flags: &i32, divisibility: &i32) -> Result<(), ArtifactError> {
+    let new_token = NewToken {
+        token,
+        flags,
+        divisibility,
+        owner: None,
+        issuer: None,
+        issued_at_height: None,
+        issuance_txid: None,
+        total_supply: &0,
+        parent: None,
+    };
+
+    create_issued_token(conn, &new_token)
+}
 
-    diesel::insert_into(tokens::table)
-        .values(&new_token)
-        .execute(conn)
-        .map(|_| ())
+/// Insert DB
+pub fn create_issued_token(conn: &mut DbConnection, new_token: &NewToken) -> Result<(), ArtifactError> {
+    use diesel::result::{DatabaseErrorKind, Error};
+
+    let inserted = with_backend!(conn, |conn| {
+        diesel::insert_into(tokens::table)
+            .values(new_token)
+            .execute(conn)
+    });
+
+    match inserted {
+        Err(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            Err(ArtifactError::AlreadyExists(format!("Token {}", new_token.token)))
+        }
+        inserted => inserted.map(|_| ()).map_err(ArtifactError::from),
+    }
 }
 
 /// Update DB
-fn update_token(conn: &mut SqliteConnection, token: &str, new_flags: &i32) -> Result<(), diesel::result::Error> {
+pub fn update_token(conn: &mut DbConnection, token: &str, new_flags: &i32) -> Result<(), ArtifactError> {
     use crate::models::token::tokens::dsl::flags;
     use crate::models::token::tokens::dsl::tokens;
 
-    diesel::update(tokens.find(token))
-        .set(flags.eq(new_flags))
-        .execute(conn)
-        .map(|_| ())
+    let updated = with_backend!(conn, |conn| {
+        diesel::update(tokens.find(token))
+            .set(flags.eq(new_flags))
+            .execute(conn)
+    })?;
+
+    require_updated(token, updated)
+}
+
+/// Update DB
+pub fn update_supply(conn: &mut DbConnection, token: &str, new_total_supply: &i64) -> Result<(), ArtifactError> {
+    use crate::models::token::tokens::dsl::total_supply;
+    use crate::models::token::tokens::dsl::tokens;
+
+    let updated = with_backend!(conn, |conn| {
+        diesel::update(tokens.find(token))
+            .set(total_supply.eq(new_total_supply))
+            .execute(conn)
+    })?;
+
+    require_updated(token, updated)
+}
+
+/// Update DB
+pub fn update_owner(conn: &mut DbConnection, token: &str, new_owner: &str) -> Result<(), ArtifactError> {
+    use crate::models::token::tokens::dsl::owner;
+    use crate::models::token::tokens::dsl::tokens;
+
+    let updated = with_backend!(conn, |conn| {
+        diesel::update(tokens.find(token))
+            .set(owner.eq(new_owner))
+            .execute(conn)
+    })?;
+
+    require_updated(token, updated)
+}
+
+/// Validation (an update found the token)
+fn require_updated(token: &str, updated: usize) -> Result<(), ArtifactError> {
+    match updated {
+        0 => Err(ArtifactError::NotFound(format!("Token {}", token))),
+        _ => Ok(()),
+    }
 }
 
 /// Filter DB
-fn fetch_token(conn: &mut SqliteConnection, token_name: &str) -> Result<Token, diesel::result::Error> {
+pub fn fetch_token(conn: &mut DbConnection, token_name: &str) -> Result<Option<Token>, ArtifactError> {
     use crate::models::token::tokens::dsl::*;
 
-    match tokens.filter(token.eq(token_name)).first::<Token>(conn) {
-        Ok(matched_token) => Ok(matched_token),
-        Err(diesel::result::Error::NotFound) => Err(diesel::result::Error::NotFound),
-        Err(e) => Err(e),
-    }
+    with_backend!(conn, |conn| {
+        tokens.filter(token.eq(token_name)).first::<Token>(conn).optional().map_err(ArtifactError::from)
+    })
 }
 
 /// Filter DB
-fn token_exists(conn: &mut SqliteConnection, token_name: &str) -> Result<bool, diesel::result::Error> {
+pub fn fetch_subtokens(conn: &mut DbConnection, parent_name: &str) -> Result<Vec<Token>, ArtifactError> {
     use crate::models::token::tokens::dsl::*;
 
-    let exists = tokens
-        .filter(token.eq(token_name))
-        .first::<Token>(conn)
-        .optional()?
-        .is_some();
+    with_backend!(conn, |conn| {
+        tokens
+            .filter(parent.eq(parent_name))
+            .order(token.asc())
+            .load::<Token>(conn)
+            .map_err(ArtifactError::from)
+    })
+}
+
+/// Filter DB
+pub fn token_exists(conn: &mut DbConnection, token_name: &str) -> Result<bool, ArtifactError> {
+    use crate::models::token::tokens::dsl::*;
+
+    let exists = with_backend!(conn, |conn| {
+        tokens
+            .filter(token.eq(token_name))
+            .first::<Token>(conn)
+            .optional()?
+            .is_some()
+    });
 
     Ok(exists)
 }
 
 /// Generation
-fn generate_id(token: &str) -> u64 {
+pub fn generate_id(token: &str) -> Result<u64, ArtifactError> {
     // From Token to ID #
     let mut id: u64 = 0;
 
     for c in token.chars() {
-        let n = VALID_CHARACTERS.iter().position(|&p| p == c).unwrap() as u64;
-
-        id *= 38;
-        id += n;
+        let n = VALID_CHARACTERS.iter().position(|&p| p == c).ok_or_else(|| {
+            ArtifactError::validation(Reason::InvalidTokenCharacters, format!("'{}' is not a token character", c))
+        })? as u64;
+
+        id = id
+            .checked_mul(38)
+            .and_then(|id| id.checked_add(n))
+            .ok_or_else(|| ArtifactError::validation(Reason::InvalidTokenLength, "Must be between 3 and 12"))?;
     }
 
-    id
+    Ok(id)
 }
 
 /// Translation
-fn generate_token(id: u64) -> String {
+pub fn generate_token(id: u64) -> String {
     use num_integer::Integer;
 
     // From ID # to Token
@@ -142,11 +434,12 @@ fn generate_token(id: u64) -> String {
 }
 
 /// Validation
-fn validate_id(id: u64) -> Result<(), ValidationError> {
+pub fn validate_id(id: u64) -> Result<(), ArtifactError> {
     // Validate id range (AAA - 999999999999)
-    if id < 2966 || id > 9065737908494995455 {
-        Err(ValidationError::new(
-            "InvalidTokenLength: Must be between 3 and 12",
+    if !(2966..=9065737908494995455).contains(&id) {
+        Err(ArtifactError::validation(
+            Reason::InvalidTokenId,
+            format!("{} is outside AAA to 999999999999", id),
         ))
     } else {
         Ok(())
@@ -154,124 +447,292 @@ fn validate_id(id: u64) -> Result<(), ValidationError> {
 }
 
 /// Validation
-fn validate_token(token: &str) -> Result<(), ValidationError> {
+pub(crate) fn check_token(token: &str) -> Result<(), ArtifactError> {
     // Length between 3 and 12
     if token.len() < 3 || token.len() > 12 {
-        Err(ValidationError::new(
-            "InvalidTokenLength: Must be between 3 and 12",
-        ))
+        Err(ArtifactError::validation(Reason::InvalidTokenLength, "Must be between 3 and 12"))
     // Token minimum length 3
     } else if token.split('.').next().unwrap().len() < 3 {
-        Err(ValidationError::new(
-            "InvalidTokenLength: Minimum token length is 3",
-        ))
+        Err(ArtifactError::validation(Reason::InvalidTokenLength, "Minimum token length is 3"))
     // Subtoken min. length 5
     } else if token.contains('.') && token.len() < 5 {
-        Err(ValidationError::new(
-            "InvalidTokenLength: Minimum subtoken length is 5",
-        ))
+        Err(ArtifactError::validation(Reason::InvalidTokenLength, "Minimum subtoken length is 5"))
     // Subtokens one level max
     } else if token.split('.').count() > 2 {
-        Err(ValidationError::new(
-            "InvalidTokenLength: Maximum subtoken level is 1",
-        ))
+        Err(ArtifactError::validation(Reason::InvalidTokenLength, "Maximum subtoken level is 1"))
     // First character NOT "."
     } else if token.chars().next().unwrap() == VALID_CHARACTERS[0] {
-        Err(ValidationError::new(
-            "InvalidTokenCharacters: First character cannot be '.'",
-        ))
+        Err(ArtifactError::validation(Reason::InvalidTokenCharacters, "First character cannot be '.'"))
     // Final character NOT "."
     } else if token.chars().last().unwrap() == VALID_CHARACTERS[0] {
-        Err(ValidationError::new(
-            "InvalidTokenCharacters: Last character cannot be '.'",
-        ))
+        Err(ArtifactError::validation(Reason::InvalidTokenCharacters, "Last character cannot be '.'"))
     // Hyphens for ITNs ONLY
     } else if token.contains('-') && (!token.starts_with("XN--") || token.ends_with('-')) {
-        return Err(ValidationError::new("InvalidTokenCharacters: Hyphens for IDN only."));
+        Err(ArtifactError::validation(Reason::InvalidTokenCharacters, "Hyphens for IDN only."))
+    // IDNs must be valid punycode
+    } else if token.starts_with("XN--") && idn::decode_punycode(&token.split('.').next().unwrap()[4..]).is_none() {
+        Err(ArtifactError::validation(Reason::InvalidTokenCharacters, "Invalid IDN encoding."))
     // Not BTC or its subtoken
-    } else if token == "BTC" || token.len() >= 4 && &token[..4] == "BTC." {
-        Err(ValidationError::new(
-            "InvalidTokenCharacters: Cannot issue BTC as a token.",
-        ))
+    } else if token == "BTC" || token.starts_with("BTC.") {
+        Err(ArtifactError::validation(Reason::InvalidTokenCharacters, "Cannot issue BTC as a token."))
     // Not ART or its subtoken
-    } else if token == "ART" || token.len() >= 4 && &token[..4] == "ART." {
-        Err(ValidationError::new(
-            "InvalidTokenCharacters: Cannot issue ART as a token.",
-        ))
+    } else if token == "ART" || token.starts_with("ART.") {
+        Err(ArtifactError::validation(Reason::InvalidTokenCharacters, "Cannot issue ART as a token."))
     // All characters UPPERCASE
     } else if !token
-        .replace('.', "")
-        .replace('-', "")
+        .replace(['.', '-'], "")
         .chars()
         .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
     {
-        Err(ValidationError::new(
-            "InvalidTokenLetterCase: Must be uppercase characters.",
-        ))
+        Err(ArtifactError::validation(Reason::InvalidTokenLetterCase, "Must be uppercase characters."))
     // All characters ALPHA NUM
     } else if !token
-        .replace('.', "")
-        .replace('-', "")
+        .replace(['.', '-'], "")
         .chars()
         .all(|c| c.is_ascii_alphanumeric())
     {
-        Err(ValidationError::new(
-            "InvalidTokenCharacters: Must be ascii alphanumeric.",
-        ))
+        Err(ArtifactError::validation(Reason::InvalidTokenCharacters, "Must be ascii alphanumeric."))
     // All characters are valid
     } else if !token.chars().all(|c| VALID_CHARACTERS.contains(&c)) {
-        Err(ValidationError::new(
-            "InvalidTokenCharacters: Must only use valid characters.",
-        ))
+        Err(ArtifactError::validation(Reason::InvalidTokenCharacters, "Must only use valid characters."))
     // Valid
     } else {
         Ok(())
     }
 }
 
+/// Validation (for `#[validate(custom)]`)
+fn validate_token(token: &str) -> Result<(), ValidationError> {
+    check_token(token).map_err(|e| {
+        let mut error = ValidationError::new(e.reason().map_or("InvalidToken", |r| r.name()));
+        error.message = Some(e.to_string().into());
+        error
+    })
+}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
-    use diesel::connection::Connection;
-    use diesel::sqlite::SqliteConnection;
-    use diesel_migrations::{run_pending_migrations, EmbeddedMigrations, MigrationHarness};
-
-    static MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
-
-    fn establish_test_connection() -> SqliteConnection {
-        let conn = SqliteConnection::establish(":memory:")
-            .expect("Failed to create an in-memory database");
-
-        // Run pending migrations
-        conn.run_pending_migrations(MIGRATIONS)
-            .expect("Failed to run migrations");
+    use crate::fixtures::establish_test_connection;
+    use crate::models::balance::fetch_quantity;
+    use crate::models::issuance::fetch_issuances;
+
+    fn origin(block_height: i32, txid: &str) -> Origin<'_> {
+        Origin {
+            block_height,
+            tx_index: 0,
+            txid,
+            message_id: Some(1),
+        }
+    }
 
-        conn
+    fn create_message_row(conn: &mut DbConnection) {
+        use crate::models::message::{create_message, NewMessage};
+
+        create_message(
+            conn,
+            &NewMessage {
+                block_height: &100,
+                tx_index: &0,
+                txid: "txid",
+                source: "issuer",
+                destination: None,
+                message_type: &20,
+                data: &[],
+            },
+        )
+        .unwrap();
     }
 
     #[test]
     fn test_create_and_update_token() {
         let mut conn = establish_test_connection();
 
-        create_token(&mut conn, "UPDATE", &0);
-        update_token(&mut conn, "UPDATE", &1);
+        create_token(&mut conn, "UPDATE", &0, &0).unwrap();
+        update_token(&mut conn, "UPDATE", &1).unwrap();
 
-        let updated_token = fetch_token(&conn, "UPDATE").unwrap();
+        let updated_token = fetch_token(&mut conn, "UPDATE").unwrap().unwrap();
 
         // Case: Updated Flags
         assert_eq!(updated_token.flags, 1);
+        // Case: Missing Token
+        assert_eq!(update_token(&mut conn, "MISSING", &1).unwrap_err().code(), 104);
     }
 
     #[test]
     fn test_token_exists() {
-        let conn = establish_test_connection();
+        let mut conn = establish_test_connection();
 
-        create_token(&mut conn, "EXISTS", &0);
+        create_token(&mut conn, "EXISTS", &0, &0).unwrap();
 
         // Case: Token Exists
-        assert!(token_exists(&conn, "EXISTS").unwrap());
+        assert!(token_exists(&mut conn, "EXISTS").unwrap());
         // Case: Doesnt Exist
-        assert!(!token_exists(&conn, "NOTEXISTS").unwrap());
+        assert!(!token_exists(&mut conn, "NOTEXISTS").unwrap());
+    }
+
+    #[test]
+    fn test_parse_records_issuer() {
+        let mut conn = establish_test_connection();
+        let token_id = generate_id("ISSUED").unwrap();
+
+        parse(&mut conn, &origin(100, "txid"), "issuer", token_id, 0, 0, 0).unwrap();
+        parse(&mut conn, &origin(101, "txid2"), "issuer", token_id, 0, 0, 1).unwrap();
+
+        let issuances = fetch_issuances(&mut conn, "ISSUED").unwrap();
+
+        // Case: Token Created
+        assert_eq!(fetch_token(&mut conn, "ISSUED").unwrap().unwrap().flags, 1);
+        // Case: Issuances Recorded
+        assert_eq!(issuances.len(), 2);
+        assert_eq!(issuances[0].issuer, "issuer");
+        assert_eq!(issuances[0].block_height, 100);
+        assert_eq!(issuances[1].txid, "txid2");
+        // Case: Invalid Name
+        assert!(parse(&mut conn, &origin(102, "txid3"), "issuer", generate_id("BTC").unwrap(), 0, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_credits_issued_quantity() {
+        let mut conn = establish_test_connection();
+        let token_id = generate_id("SUPPLY").unwrap();
+        create_message_row(&mut conn);
+
+        parse(&mut conn, &origin(100, "txid"), "issuer", token_id, 5_000_000_000, 8, 0).unwrap();
+        parse(&mut conn, &origin(101, "txid2"), "issuer", token_id, 1, 8, 0).unwrap();
+
+        // Case: Issuer Credited
+        assert_eq!(fetch_quantity(&mut conn, "issuer", "SUPPLY").unwrap(), 5_000_000_001);
+        // Case: Divisibility Stored
+        assert_eq!(fetch_token(&mut conn, "SUPPLY").unwrap().unwrap().divisibility, 8);
+        // Case: Divisibility Changed
+        assert!(parse(&mut conn, &origin(102, "txid3"), "issuer", token_id, 1, 2, 0).is_err());
+        // Case: Over i64
+        assert!(parse(&mut conn, &origin(102, "txid3"), "issuer", token_id, u64::MAX, 8, 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_tracks_owner_and_supply() {
+        let mut conn = establish_test_connection();
+        let token_id = generate_id("OWNED").unwrap();
+        create_message_row(&mut conn);
+
+        parse(&mut conn, &origin(100, "txid"), "issuer", token_id, 700, 0, 0).unwrap();
+        parse(&mut conn, &origin(101, "txid2"), "issuer", token_id, 300, 0, 0).unwrap();
+
+        let owned = fetch_token(&mut conn, "OWNED").unwrap().unwrap();
+
+        // Case: First Issuance Recorded
+        assert_eq!(owned.owner.as_deref(), Some("issuer"));
+        assert_eq!(owned.issuer.as_deref(), Some("issuer"));
+        assert_eq!(owned.issued_at_height, Some(100));
+        assert_eq!(owned.issuance_txid.as_deref(), Some("txid"));
+        // Case: Supply Accumulated
+        assert_eq!(owned.total_supply, 1_000);
+        // Case: Not The Owner
+        assert!(parse(&mut conn, &origin(102, "txid3"), "mallory", token_id, 1, 0, 0).is_err());
+        // Case: New Owner Issues
+        update_owner(&mut conn, "OWNED", "mallory").unwrap();
+        assert!(parse(&mut conn, &origin(102, "txid3"), "issuer", token_id, 1, 0, 0).is_err());
+        parse(&mut conn, &origin(102, "txid3"), "mallory", token_id, 1, 0, 0).unwrap();
+        assert_eq!(fetch_token(&mut conn, "OWNED").unwrap().unwrap().issuer.as_deref(), Some("issuer"));
+        assert_eq!(fetch_token(&mut conn, "OWNED").unwrap().unwrap().total_supply, 1_001);
+    }
+
+    #[test]
+    fn test_parse_enforces_locked_flag() {
+        let mut conn = establish_test_connection();
+        let token_id = generate_id("FINAL").unwrap();
+        let (namespace, locked) = (Flags::NAMESPACE.bits(), Flags::LOCKED.bits());
+        create_message_row(&mut conn);
+
+        parse(&mut conn, &origin(100, "txid"), "issuer", token_id, 100, 0, namespace).unwrap();
+
+        // Case: Clearing A Flag
+        assert_eq!(
+            parse(&mut conn, &origin(101, "txid2"), "issuer", token_id, 0, 0, 0).unwrap_err().reason(),
+            Some(Reason::InvalidFlags)
+        );
+
+        parse(&mut conn, &origin(101, "txid2"), "issuer", token_id, 0, 0, namespace | locked).unwrap();
+
+        // Case: Issuing After Lock
+        assert_eq!(
+            parse(&mut conn, &origin(102, "txid3"), "issuer", token_id, 1, 0, namespace | locked).unwrap_err().reason(),
+            Some(Reason::TokenLocked)
+        );
+        // Case: Unlocking
+        assert!(parse(&mut conn, &origin(102, "txid3"), "issuer", token_id, 0, 0, namespace).is_err());
+        // Case: Supply Final
+        let locked_token = fetch_token(&mut conn, "FINAL").unwrap().unwrap();
+        assert_eq!(locked_token.flags, namespace | locked);
+        assert_eq!(locked_token.total_supply, 100);
+    }
+
+    #[test]
+    fn test_parse_subtoken_requires_namespace_owner() {
+        let mut conn = establish_test_connection();
+        let namespace = Flags::NAMESPACE.bits();
+
+        parse(&mut conn, &origin(100, "txid"), "issuer", generate_id("ABC").unwrap(), 0, 0, 0).unwrap();
+        parse(&mut conn, &origin(100, "txid"), "issuer", generate_id("DEF").unwrap(), 0, 0, namespace).unwrap();
+
+        // Case: Parent Missing
+        assert_eq!(
+            parse(&mut conn, &origin(101, "txid2"), "issuer", generate_id("XYZ.ONE").unwrap(), 0, 0, 0).unwrap_err().reason(),
+            Some(Reason::UnknownToken)
+        );
+        // Case: Parent Without Namespace
+        assert_eq!(
+            parse(&mut conn, &origin(101, "txid2"), "issuer", generate_id("ABC.ONE").unwrap(), 0, 0, 0).unwrap_err().reason(),
+            Some(Reason::InvalidNamespace)
+        );
+        // Case: Not The Parent Owner
+        assert_eq!(
+            parse(&mut conn, &origin(101, "txid2"), "mallory", generate_id("DEF.ONE").unwrap(), 0, 0, 0).unwrap_err().reason(),
+            Some(Reason::InvalidOwner)
+        );
+
+        parse(&mut conn, &origin(101, "txid2"), "issuer", generate_id("DEF.ONE").unwrap(), 0, 0, 0).unwrap();
+        parse(&mut conn, &origin(101, "txid2"), "issuer", generate_id("DEF.TWO").unwrap(), 0, 0, 0).unwrap();
+
+        // Case: Parent Recorded
+        assert_eq!(fetch_token(&mut conn, "DEF.ONE").unwrap().unwrap().parent.as_deref(), Some("DEF"));
+        // Case: Namespace Listing
+        let children = fetch_subtokens(&mut conn, "DEF").unwrap();
+        assert_eq!(
+            children.iter().map(|t| t.token.as_str()).collect::<Vec<_>>(),
+            vec!["DEF.ONE", "DEF.TWO"]
+        );
+        assert!(fetch_subtokens(&mut conn, "ABC").unwrap().is_empty());
+    }
+
+    #[test]
+    fn format_quantity_uses_divisibility() {
+        // Case: Indivisible
+        assert_eq!(format_quantity(42, 0), "42");
+        // Case: Satoshi Style
+        assert_eq!(format_quantity(150_000_000, 8), "1.50000000");
+        // Case: Fraction Only
+        assert_eq!(format_quantity(5, 2), "0.05");
+    }
+
+    #[test]
+    fn parse_quantity_uses_divisibility() {
+        // Case: Whole Units
+        assert_eq!(parse_quantity("42", 0).unwrap(), 42);
+        // Case: Short Fraction
+        assert_eq!(parse_quantity("1.5", 8).unwrap(), 150_000_000);
+        // Case: Round Trip
+        assert_eq!(parse_quantity(&format_quantity(12345, 3), 3).unwrap(), 12345);
+        // Case: Too Precise
+        assert!(parse_quantity("1.234", 2).is_err());
+        // Case: Not A Number
+        assert!(parse_quantity("-1", 2).is_err());
+        assert!(parse_quantity(".5", 2).is_err());
+        // Case: Above i64
+        assert!(parse_quantity("9223372036854775808", 0).is_err());
     }
 
     #[test]
@@ -0,0 +1,298 @@
+use crate::error::ArtifactError;
+use crate::options::NotifyOptions;
+use bitcoin::{BlockHash, Transaction};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Notifications queued while the indexer is busy (later ones are dropped,
+/// they are only hints and polling catches up)
+const QUEUE_CAPACITY: usize = 1_000;
+
+/// Something the node announced
+#[derive(Debug)]
+pub enum Notification {
+    Block(BlockHash),
+    Transaction(Transaction),
+}
+
+/// Tip Notifier
+///
+/// Tells the indexer when to look for new blocks: as soon as bitcoind
+/// publishes a `hashblock` over ZMQ (with the `zmq` feature), otherwise
+/// every `poll_interval`. Polling continues alongside ZMQ, which drops
+/// messages when the subscriber is slow or reconnecting.
+pub struct Notifier {
+    receiver: Receiver<Notification>,
+    poll_interval: Duration,
+    stopped: Arc<AtomicBool>,
+    subscribers: Vec<JoinHandle<()>>,
+}
+
+impl Notifier {
+    pub fn new(options: &NotifyOptions) -> Result<Self, ArtifactError> {
+        let (sender, receiver) = sync_channel(QUEUE_CAPACITY);
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        // One socket per endpoint, subscribed to the topics published there
+        let mut endpoints: Vec<(&str, Vec<&str>)> = vec![];
+        for (url, topic) in [(&options.zmq_hashblock, "hashblock"), (&options.zmq_rawtx, "rawtx")] {
+            if let Some(url) = url {
+                match endpoints.iter_mut().find(|(endpoint, _)| endpoint == url) {
+                    Some((_, topics)) => topics.push(topic),
+                    None => endpoints.push((url, vec![topic])),
+                }
+            }
+        }
+
+        let subscribers = endpoints
+            .into_iter()
+            .map(|(url, topics)| subscribe(url, &topics, sender.clone(), Arc::clone(&stopped)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            receiver,
+            poll_interval: options.poll_interval,
+            stopped,
+            subscribers,
+        })
+    }
+
+    /// Notifier that only polls, with the sender for feeding it notifications
+    pub fn polling(poll_interval: Duration) -> (Self, SyncSender<Notification>) {
+        let (sender, receiver) = sync_channel(QUEUE_CAPACITY);
+
+        let notifier = Self {
+            receiver,
+            poll_interval,
+            stopped: Arc::new(AtomicBool::new(false)),
+            subscribers: vec![],
+        };
+
+        (notifier, sender)
+    }
+
+    /// Block until a new block is announced (`Some`) or the poll interval
+    /// passes (`None`), handing announced transactions to `on_transaction`
+    pub fn wait<F: FnMut(Transaction)>(&self, mut on_transaction: F) -> Option<BlockHash> {
+        let deadline = Instant::now() + self.poll_interval;
+
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+
+            match self.receiver.recv_timeout(timeout) {
+                Ok(Notification::Block(hash)) => return Some(hash),
+                Ok(Notification::Transaction(tx)) => on_transaction(tx),
+                Err(RecvTimeoutError::Timeout) => return None,
+                // Every subscriber is gone, sleep out the interval instead
+                Err(RecvTimeoutError::Disconnected) => {
+                    std::thread::sleep(timeout);
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+
+        for subscriber in self.subscribers.drain(..) {
+            let _ = subscriber.join();
+        }
+    }
+}
+
+/// Decode one ZMQ message: topic, body, little endian sequence number
+#[cfg(feature = "zmq")]
+fn decode(parts: &[Vec<u8>]) -> Result<Notification, String> {
+    use bitcoin::consensus::deserialize;
+    use bitcoin::hashes::Hash;
+
+    match parts {
+        [topic, body, ..] if topic == b"hashblock" => {
+            // Published in display (reversed) byte order
+            let mut bytes: [u8; 32] = body.as_slice().try_into().map_err(|_| "hashblock must be 32 bytes".to_string())?;
+            bytes.reverse();
+            Ok(Notification::Block(BlockHash::from_byte_array(bytes)))
+        }
+        [topic, body, ..] if topic == b"rawtx" => deserialize(body)
+            .map(Notification::Transaction)
+            .map_err(|e| format!("rawtx: {}", e)),
+        _ => Err(format!("unexpected message with {} parts", parts.len())),
+    }
+}
+
+/// Queue `notification` without blocking the subscriber, dropping it when
+/// the queue is full (`false` once the notifier is gone)
+#[cfg(feature = "zmq")]
+fn forward(sender: &SyncSender<Notification>, notification: Notification) -> bool {
+    use std::sync::mpsc::TrySendError;
+
+    !matches!(sender.try_send(notification), Err(TrySendError::Disconnected(_)))
+}
+
+/// Subscribe to `topics` at `url` on a background thread
+#[cfg(feature = "zmq")]
+fn subscribe(
+    url: &str,
+    topics: &[&str],
+    sender: SyncSender<Notification>,
+    stopped: Arc<AtomicBool>,
+) -> Result<JoinHandle<()>, ArtifactError> {
+    let zmq_error = |e: ::zmq::Error| ArtifactError::Configuration(format!("ZMQ {}: {}", url, e));
+
+    let socket = ::zmq::Context::new().socket(::zmq::SUB).map_err(zmq_error)?;
+    // Wake up regularly to notice the notifier was dropped
+    socket.set_rcvtimeo(250).map_err(zmq_error)?;
+    socket.connect(url).map_err(zmq_error)?;
+    for topic in topics {
+        socket.set_subscribe(topic.as_bytes()).map_err(zmq_error)?;
+    }
+
+    let url = url.to_string();
+    Ok(std::thread::spawn(move || {
+        while !stopped.load(Ordering::SeqCst) {
+            let parts = match socket.recv_multipart(0) {
+                Ok(parts) => parts,
+                Err(::zmq::Error::EAGAIN) => continue,
+                Err(e) => {
+                    eprintln!("ZMQ {}: {}, falling back to polling", url, e);
+                    return;
+                }
+            };
+
+            match decode(&parts) {
+                Ok(notification) => {
+                    if !forward(&sender, notification) {
+                        return;
+                    }
+                }
+                Err(e) => eprintln!("ZMQ {}: {}", url, e),
+            }
+        }
+    }))
+}
+
+#[cfg(not(feature = "zmq"))]
+fn subscribe(
+    url: &str,
+    _topics: &[&str],
+    _sender: SyncSender<Notification>,
+    _stopped: Arc<AtomicBool>,
+) -> Result<JoinHandle<()>, ArtifactError> {
+    Err(ArtifactError::Configuration(format!(
+        "ZMQ endpoint {} is set but artifact was built without the zmq feature",
+        url
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use bitcoin::hashes::Hash;
+
+    #[test]
+    fn wait_wakes_on_blocks_and_polls() {
+        let (notifier, sender) = Notifier::polling(Duration::from_millis(20));
+        let tx = fixtures::transaction(1, vec![]);
+        let hash = fixtures::block(BlockHash::all_zeros(), vec![]).block_hash();
+
+        // Case: Poll Interval Passes
+        assert!(notifier.wait(|_| panic!("no transactions")).is_none());
+
+        sender.send(Notification::Transaction(tx.clone())).unwrap();
+        sender.send(Notification::Block(hash)).unwrap();
+        let mut seen = vec![];
+
+        // Case: Woken By Block
+        assert_eq!(notifier.wait(|tx| seen.push(tx)), Some(hash));
+        // Case: Transactions Handed Over
+        assert_eq!(seen, vec![tx]);
+
+        // Case: Nothing Left To Notify
+        drop(sender);
+        assert!(notifier.wait(|_| ()).is_none());
+    }
+
+    #[cfg(not(feature = "zmq"))]
+    #[test]
+    fn new_requires_the_zmq_feature() {
+        let options = NotifyOptions {
+            zmq_hashblock: Some("tcp://127.0.0.1:28332".to_string()),
+            zmq_rawtx: None,
+            poll_interval: Duration::from_secs(1),
+        };
+
+        // Case: Endpoint Without Feature
+        assert_eq!(Notifier::new(&options).err().unwrap().code(), 102);
+    }
+
+    #[cfg(feature = "zmq")]
+    #[test]
+    fn forward_drops_when_full() {
+        let (notifier, sender) = Notifier::polling(Duration::from_millis(20));
+        let hash = fixtures::block(BlockHash::all_zeros(), vec![]).block_hash();
+
+        // Case: Full Queue Doesn't Block
+        for _ in 0..=QUEUE_CAPACITY {
+            assert!(forward(&sender, Notification::Block(hash)));
+        }
+        assert!(notifier.wait(|_| ()).is_some());
+
+        // Case: Notifier Gone
+        drop(notifier);
+        assert!(!forward(&sender, Notification::Block(hash)));
+    }
+
+    #[cfg(feature = "zmq")]
+    #[test]
+    fn new_subscribes_to_zmq_publisher() {
+        use bitcoin::consensus::serialize;
+
+        // Local stand-in for bitcoind's publisher
+        let publisher = ::zmq::Context::new().socket(::zmq::PUB).unwrap();
+        publisher.bind("tcp://127.0.0.1:*").unwrap();
+        let url = publisher.get_last_endpoint().unwrap().unwrap();
+
+        let notifier = Notifier::new(&NotifyOptions {
+            zmq_hashblock: Some(url.clone()),
+            zmq_rawtx: Some(url),
+            poll_interval: Duration::from_millis(50),
+        })
+        .unwrap();
+
+        let tx = fixtures::transaction(1, vec![]);
+        let hash = fixtures::block(BlockHash::all_zeros(), vec![]).block_hash();
+        let mut reversed = hash.to_byte_array();
+        reversed.reverse();
+
+        // Subscriptions take a moment to reach the publisher
+        let mut seen = vec![];
+        let mut woken = None;
+        for sequence in 0u32..100 {
+            publisher
+                .send_multipart([b"rawtx".to_vec(), serialize(&tx), sequence.to_le_bytes().to_vec()], 0)
+                .unwrap();
+            publisher
+                .send_multipart([b"hashblock".to_vec(), reversed.to_vec(), sequence.to_le_bytes().to_vec()], 0)
+                .unwrap();
+
+            woken = notifier.wait(|tx| seen.push(tx));
+            if woken.is_some() && !seen.is_empty() {
+                break;
+            }
+        }
+
+        // Case: Woken By hashblock
+        assert_eq!(woken, Some(hash));
+        // Case: rawtx Decoded
+        assert_eq!(seen.last(), Some(&tx));
+        // Case: Dropped While Subscribed
+        drop(notifier);
+    }
+}
@@ -1,33 +1,228 @@
-use bitcoincore_rpc::{Auth, Client};
+use crate::error::ArtifactError;
+use crate::source::rest::RestClient;
+use bitcoin::Network;
+use bitcoincore_rpc::{Auth, Client, RpcApi};
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// How blocks are fetched from the node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockApi {
+    /// JSON-RPC, authenticated
+    Rpc,
+    /// REST (`-rest`), raw binary blocks without authentication
+    Rest,
+}
 
 pub struct BitcoinRpcOptions {
+    pub network: Network,
+    pub block_api: BlockApi,
     pub rpc_url: String,
     pub rpc_user: Option<String>,
     pub rpc_pass: Option<String>,
     pub cookie_file: Option<PathBuf>,
+    /// bitcoind's `blocks/` directory, read directly for the initial sync
+    pub blocks_dir: Option<PathBuf>,
 }
 
 impl BitcoinRpcOptions {
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self, ArtifactError> {
         dotenvy::dotenv().ok();
 
-        Self {
-            rpc_url: env::var("BITCOIN_RPC_URL").unwrap_or_else(|_| "http://localhost:8332".to_string()),
+        let network = match env::var("BITCOIN_NETWORK") {
+            Ok(name) => parse_network(&name)?,
+            Err(_) => Network::Bitcoin,
+        };
+
+        let block_api = match env::var("BITCOIN_BLOCK_API") {
+            Ok(name) => parse_block_api(&name)?,
+            Err(_) => BlockApi::Rpc,
+        };
+
+        Ok(Self {
+            network,
+            block_api,
+            rpc_url: env::var("BITCOIN_RPC_URL")
+                .unwrap_or_else(|_| format!("http://localhost:{}", default_rpc_port(network))),
             rpc_user: env::var("BITCOIN_RPC_USER").ok(),
             rpc_pass: env::var("BITCOIN_RPC_PASS").ok(),
             cookie_file: env::var("BITCOIN_RPC_COOKIE_FILE").ok().map(PathBuf::from),
-        }
+            blocks_dir: env::var("BITCOIN_BLOCKS_DIR").ok().map(PathBuf::from),
+        })
     }
 
-    pub fn create_rpc_client(&self) -> bitcoincore_rpc::Result<Client> {
+    pub fn create_rpc_client(&self) -> Result<Client, ArtifactError> {
         let auth = match (&self.rpc_user, &self.rpc_pass, &self.cookie_file) {
             (Some(user), Some(pass), _) => Auth::UserPass(user.clone(), pass.clone()),
             (_, _, Some(cookie_file)) => Auth::CookieFile(cookie_file.clone()),
-            _ => return Err(bitcoincore_rpc::Error::ReturnedError("Missing authentication data for RPC client".into())),
+            _ => {
+                return Err(ArtifactError::Configuration(
+                    "Missing authentication data for RPC client".to_string(),
+                ))
+            }
         };
 
-        Client::new(&self.rpc_url, auth)
+        Ok(Client::new(&self.rpc_url, auth)?)
+    }
+
+    /// REST client on the RPC port
+    pub fn create_rest_client(&self) -> Result<RestClient, ArtifactError> {
+        RestClient::new(&self.rpc_url)
+    }
+
+    /// Validation (the node must follow the configured chain)
+    pub fn check_network(&self, client: &Client) -> Result<(), ArtifactError> {
+        self.check_chain(client.get_blockchain_info()?.chain)
+    }
+
+    /// Validation (as `check_network`, asked over REST)
+    pub fn check_rest_network(&self, client: &RestClient) -> Result<(), ArtifactError> {
+        self.check_chain(client.chain_info()?.network)
+    }
+
+    fn check_chain(&self, chain: Network) -> Result<(), ArtifactError> {
+        if chain != self.network {
+            return Err(ArtifactError::Configuration(format!(
+                "BITCOIN_NETWORK is {} but the node at {} follows {}",
+                self.network, self.rpc_url, chain
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub struct PrefetchOptions {
+    /// Threads fetching blocks ahead of the writer
+    pub workers: usize,
+    /// Blocks fetched but not yet applied, at most
+    pub queue_depth: usize,
+}
+
+impl PrefetchOptions {
+    pub fn new() -> Result<Self, ArtifactError> {
+        dotenvy::dotenv().ok();
+
+        Ok(Self {
+            workers: parse_count("ARTIFACT_PREFETCH_WORKERS", 4)?,
+            queue_depth: parse_count("ARTIFACT_PREFETCH_DEPTH", 16)?,
+        })
+    }
+}
+
+pub struct NotifyOptions {
+    /// bitcoind's `zmqpubhashblock` endpoint
+    pub zmq_hashblock: Option<String>,
+    /// bitcoind's `zmqpubrawtx` endpoint
+    pub zmq_rawtx: Option<String>,
+    /// Check for new blocks this often, with or without ZMQ
+    pub poll_interval: Duration,
+}
+
+impl NotifyOptions {
+    pub fn new() -> Result<Self, ArtifactError> {
+        dotenvy::dotenv().ok();
+
+        Ok(Self {
+            zmq_hashblock: env::var("BITCOIN_ZMQ_HASHBLOCK").ok(),
+            zmq_rawtx: env::var("BITCOIN_ZMQ_RAWTX").ok(),
+            poll_interval: Duration::from_secs(parse_count("ARTIFACT_POLL_INTERVAL", 30)? as u64),
+        })
+    }
+}
+
+/// Parsing (a positive count, `default` when unset)
+fn parse_count(name: &str, default: usize) -> Result<usize, ArtifactError> {
+    match env::var(name) {
+        Ok(value) => value
+            .parse::<usize>()
+            .ok()
+            .filter(|count| *count > 0)
+            .ok_or_else(|| ArtifactError::Configuration(format!("{} must be a positive number, got '{}'", name, value))),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Parsing (mainnet, testnet, signet or regtest)
+pub fn parse_network(name: &str) -> Result<Network, ArtifactError> {
+    match name.to_lowercase().as_str() {
+        "mainnet" | "main" | "bitcoin" => Ok(Network::Bitcoin),
+        "testnet" | "test" => Ok(Network::Testnet),
+        "signet" => Ok(Network::Signet),
+        "regtest" => Ok(Network::Regtest),
+        _ => Err(ArtifactError::Configuration(format!(
+            "Unknown BITCOIN_NETWORK '{}', expected mainnet, testnet, signet or regtest",
+            name
+        ))),
+    }
+}
+
+/// Parsing (rpc or rest)
+pub fn parse_block_api(name: &str) -> Result<BlockApi, ArtifactError> {
+    match name.to_lowercase().as_str() {
+        "rpc" => Ok(BlockApi::Rpc),
+        "rest" => Ok(BlockApi::Rest),
+        _ => Err(ArtifactError::Configuration(format!(
+            "Unknown BITCOIN_BLOCK_API '{}', expected rpc or rest",
+            name
+        ))),
+    }
+}
+
+/// Bitcoin Core's default RPC port
+pub fn default_rpc_port(network: Network) -> u16 {
+    match network {
+        Network::Testnet => 18332,
+        Network::Signet => 38332,
+        Network::Regtest => 18443,
+        _ => 8332,
+    }
+}
+
+pub struct DatabaseOptions {
+    pub database_url: Option<String>,
+    /// Apply pending migrations on connect (otherwise refuse to start)
+    pub auto_migrate: bool,
+}
+
+impl DatabaseOptions {
+    pub fn new() -> Self {
+        dotenvy::dotenv().ok();
+
+        Self {
+            database_url: env::var("DATABASE_URL").ok(),
+            auto_migrate: env::var("ARTIFACT_AUTO_MIGRATE")
+                .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no"))
+                .unwrap_or(true),
+        }
+    }
+}
+
+impl Default for DatabaseOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_network_picks_default_port() {
+        // Case: Names And Aliases
+        assert_eq!(parse_network("mainnet").unwrap(), Network::Bitcoin);
+        assert_eq!(parse_network("Testnet").unwrap(), Network::Testnet);
+        assert_eq!(parse_network("signet").unwrap(), Network::Signet);
+        assert_eq!(parse_network("regtest").unwrap(), Network::Regtest);
+        // Case: Unknown Network
+        assert_eq!(parse_network("litecoin").unwrap_err().code(), 102);
+        // Case: Ports
+        assert_eq!(default_rpc_port(Network::Bitcoin), 8332);
+        assert_eq!(default_rpc_port(Network::Regtest), 18443);
+        // Case: Block Api
+        assert_eq!(parse_block_api("REST").unwrap(), BlockApi::Rest);
+        assert!(parse_block_api("zmq").is_err());
     }
 }
@@ -0,0 +1,197 @@
+use crate::error::ArtifactError;
+use crate::options::PrefetchOptions;
+use bitcoin::Block;
+use std::collections::BTreeMap;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Fetched = (u32, Result<Option<Block>, ArtifactError>);
+
+/// Heights handed out to workers, and how far the writer has consumed
+struct Window {
+    next_claim: u32,
+    next_consume: u32,
+    stopped: bool,
+}
+
+/// Block Prefetcher
+///
+/// Worker threads fetch blocks ahead of the ledger writer, which receives
+/// them strictly in height order. At most `queue_depth` heights are in
+/// flight (claimed but not yet consumed), so a slow block holds the
+/// workers back instead of growing the reorder buffer.
+pub struct Prefetcher {
+    receiver: Option<Receiver<Fetched>>,
+    window: Arc<(Mutex<Window>, Condvar)>,
+    pending: BTreeMap<u32, Result<Option<Block>, ArtifactError>>,
+    next: u32,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Prefetcher {
+    /// Start fetching from `height`, `fetch` returns `None` past the tip
+    pub fn start<F>(height: u32, options: &PrefetchOptions, fetch: F) -> Self
+    where
+        F: Fn(u32) -> Result<Option<Block>, ArtifactError> + Send + Sync + 'static,
+    {
+        let (sender, receiver) = sync_channel(options.queue_depth);
+        let window = Arc::new((
+            Mutex::new(Window {
+                next_claim: height,
+                next_consume: height,
+                stopped: false,
+            }),
+            Condvar::new(),
+        ));
+        let fetch = Arc::new(fetch);
+        let queue_depth = options.queue_depth as u32;
+
+        let workers = (0..options.workers)
+            .map(|_| {
+                let (sender, window, fetch) = (sender.clone(), Arc::clone(&window), Arc::clone(&fetch));
+
+                thread::spawn(move || loop {
+                    let height = {
+                        let (lock, ready) = &*window;
+                        let mut state = lock.lock().unwrap();
+                        // Backpressure: wait for the writer to catch up
+                        while !state.stopped && state.next_claim >= state.next_consume + queue_depth {
+                            state = ready.wait(state).unwrap();
+                        }
+                        if state.stopped {
+                            return;
+                        }
+
+                        state.next_claim += 1;
+                        state.next_claim - 1
+                    };
+
+                    if sender.send((height, fetch(height))).is_err() {
+                        return;
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            receiver: Some(receiver),
+            window,
+            pending: BTreeMap::new(),
+            next: height,
+            workers,
+        }
+    }
+
+    /// Next block in height order (`None` once past the tip)
+    pub fn next_block(&mut self) -> Result<Option<Block>, ArtifactError> {
+        loop {
+            if let Some(fetched) = self.pending.remove(&self.next) {
+                self.next += 1;
+
+                let (lock, ready) = &*self.window;
+                lock.lock().unwrap().next_consume = self.next;
+                ready.notify_all();
+
+                return fetched;
+            }
+
+            let (height, fetched) = self
+                .receiver
+                .as_ref()
+                .and_then(|receiver| receiver.recv().ok())
+                .ok_or_else(|| ArtifactError::Configuration("Block prefetch workers stopped".to_string()))?;
+            self.pending.insert(height, fetched);
+        }
+    }
+}
+
+impl Drop for Prefetcher {
+    fn drop(&mut self) {
+        let (lock, ready) = &*self.window;
+        lock.lock().unwrap().stopped = true;
+        ready.notify_all();
+
+        // Unblock workers waiting on a full queue
+        self.receiver.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use bitcoin::hashes::Hash;
+    use bitcoin::BlockHash;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    /// Empty block tagged with `height` in its timestamp
+    fn block_at(height: u32) -> Block {
+        let mut block = fixtures::block(BlockHash::all_zeros(), vec![]);
+        block.header.time = height;
+        block
+    }
+
+    fn options(workers: usize, queue_depth: usize) -> PrefetchOptions {
+        PrefetchOptions { workers, queue_depth }
+    }
+
+    #[test]
+    fn next_block_keeps_height_order() {
+        // Later heights finish first
+        let mut prefetcher = Prefetcher::start(10, &options(4, 8), |height| {
+            thread::sleep(Duration::from_millis(u64::from(20 - height % 4 * 5)));
+            Ok((height < 30).then(|| block_at(height)))
+        });
+
+        // Case: In Order
+        for height in 10..30 {
+            assert_eq!(prefetcher.next_block().unwrap().unwrap().header.time, height);
+        }
+        // Case: Past The Tip
+        assert!(prefetcher.next_block().unwrap().is_none());
+    }
+
+    #[test]
+    fn workers_stay_within_queue_depth() {
+        let consumed = Arc::new(AtomicU32::new(0));
+        let ahead = Arc::new(AtomicU32::new(0));
+        let (fetch_consumed, fetch_ahead) = (Arc::clone(&consumed), Arc::clone(&ahead));
+
+        let mut prefetcher = Prefetcher::start(0, &options(4, 3), move |height| {
+            let distance = height - fetch_consumed.load(Ordering::SeqCst);
+            fetch_ahead.fetch_max(distance, Ordering::SeqCst);
+            Ok(Some(block_at(height)))
+        });
+
+        for height in 0..50 {
+            // Slow writer
+            thread::sleep(Duration::from_millis(2));
+            prefetcher.next_block().unwrap();
+            consumed.store(height + 1, Ordering::SeqCst);
+        }
+
+        // Case: Bounded Lookahead
+        assert!(ahead.load(Ordering::SeqCst) <= 3);
+        // Case: Dropped With Workers Parked
+        drop(prefetcher);
+    }
+
+    #[test]
+    fn next_block_surfaces_fetch_errors() {
+        let mut prefetcher = Prefetcher::start(0, &options(2, 4), |height| match height {
+            2 => Err(ArtifactError::Configuration("node went away".to_string())),
+            _ => Ok(Some(block_at(height))),
+        });
+
+        // Case: Blocks Before The Error
+        assert!(prefetcher.next_block().unwrap().is_some());
+        assert!(prefetcher.next_block().unwrap().is_some());
+        // Case: Error At Its Height
+        assert!(prefetcher.next_block().is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use super::{expect_length, DecodeError};
+use crate::models::address::Flags;
+
+/// Payload Length (no payload, the source address is the subject)
+pub const LENGTH: usize = 0;
+
+/// Payload Length (flags)
+pub const OPTIONS_LENGTH: usize = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockAddress;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnlockAddress;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressOptions {
+    pub flags: i32,
+}
+
+impl LockAddress {
+    pub fn decode(payload: &[u8]) -> Result<Self, DecodeError> {
+        expect_length(payload, LENGTH)?;
+
+        Ok(Self)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+impl UnlockAddress {
+    pub fn decode(payload: &[u8]) -> Result<Self, DecodeError> {
+        expect_length(payload, LENGTH)?;
+
+        Ok(Self)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+impl AddressOptions {
+    pub fn decode(payload: &[u8]) -> Result<Self, DecodeError> {
+        expect_length(payload, OPTIONS_LENGTH)?;
+
+        // Unknown flag bits are rejected, not ignored
+        if Flags::from_bits(payload[0] as i32).is_none() {
+            return Err(DecodeError::InvalidFlags(payload[0]));
+        }
+
+        Ok(Self {
+            flags: payload[0] as i32,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        vec![self.flags as u8]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_rejects_payload() {
+        // Case: Empty
+        assert_eq!(LockAddress::decode(&[]), Ok(LockAddress));
+        assert_eq!(UnlockAddress::decode(&[]), Ok(UnlockAddress));
+        // Case: Trailing Bytes
+        assert!(LockAddress::decode(&[0]).is_err());
+        assert!(UnlockAddress::decode(&[0]).is_err());
+    }
+
+    #[test]
+    fn decode_options_rejects_unknown_flags() {
+        // Case: Memo Field
+        assert_eq!(AddressOptions::decode(&[2]), Ok(AddressOptions { flags: 2 }));
+        // Case: Unknown Bit
+        assert_eq!(AddressOptions::decode(&[4]), Err(DecodeError::InvalidFlags(4)));
+        // Case: Missing Flags
+        assert!(AddressOptions::decode(&[]).is_err());
+    }
+}
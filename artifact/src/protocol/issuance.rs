@@ -0,0 +1,93 @@
+use super::{expect_length, DecodeError};
+use crate::models::token::{Flags, MAX_DIVISIBILITY};
+
+/// Payload Length (token id + quantity + divisibility + flags)
+pub const LENGTH: usize = 18;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Issuance {
+    pub token_id: u64,
+    pub quantity: u64,
+    pub divisibility: u8,
+    pub flags: i32,
+}
+
+impl Issuance {
+    pub fn decode(payload: &[u8]) -> Result<Self, DecodeError> {
+        expect_length(payload, LENGTH)?;
+
+        let token_id = u64::from_be_bytes(payload[0..8].try_into().unwrap());
+        let quantity = u64::from_be_bytes(payload[8..16].try_into().unwrap());
+        let divisibility = payload[16];
+        let flags = payload[17];
+
+        if divisibility > MAX_DIVISIBILITY {
+            return Err(DecodeError::InvalidDivisibility(divisibility));
+        }
+
+        // Unknown flag bits are rejected, not ignored
+        if Flags::from_bits(flags as i32).is_none() {
+            return Err(DecodeError::InvalidFlags(flags));
+        }
+
+        Ok(Self {
+            token_id,
+            quantity,
+            divisibility,
+            flags: flags as i32,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut payload = self.token_id.to_be_bytes().to_vec();
+        payload.extend(self.quantity.to_be_bytes());
+        payload.push(self.divisibility);
+        payload.push(self.flags as u8);
+        payload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_rejects_unknown_flags() {
+        let mut payload = 2966u64.to_be_bytes().to_vec();
+        payload.extend(1u64.to_be_bytes());
+        payload.push(0);
+        payload.push(0b0000_0100);
+
+        // Case: Unknown Bit
+        assert_eq!(Issuance::decode(&payload), Err(DecodeError::InvalidFlags(4)));
+    }
+
+    #[test]
+    fn decode_rejects_excess_divisibility() {
+        let mut payload = 2966u64.to_be_bytes().to_vec();
+        payload.extend(1u64.to_be_bytes());
+        payload.push(9);
+        payload.push(0);
+
+        // Case: Nine Decimals
+        assert_eq!(Issuance::decode(&payload), Err(DecodeError::InvalidDivisibility(9)));
+    }
+
+    #[test]
+    fn decode_reads_big_endian_fields() {
+        let payload = [
+            0, 0, 0, 0, 0, 0, 0x0b, 0x96, 0, 0, 0, 0x01, 0, 0, 0, 0, 8, 0b0000_0011,
+        ];
+
+        // Case: AAA (2966), 2^32 units, 8 decimals
+        assert_eq!(
+            Issuance::decode(&payload),
+            Ok(Issuance {
+                token_id: 2966,
+                quantity: 1 << 32,
+                divisibility: 8,
+                flags: 3
+            })
+        );
+    }
+}
@@ -0,0 +1,405 @@
+pub mod address;
+pub mod issuance;
+pub mod send;
+pub mod transfer;
+
+use bitcoin::blockdata::script::Instruction;
+use bitcoin::{Address, Network, PublicKey, Script, Transaction};
+use std::fmt;
+
+pub use address::{AddressOptions, LockAddress, UnlockAddress};
+pub use issuance::Issuance;
+pub use send::SendMessage;
+pub use transfer::TransferOwnership;
+
+/// Envelope Prefix
+pub const PREFIX: &[u8; 3] = b"ART";
+
+/// Protocol Version (bump whenever consensus rules change, ledgers indexed
+/// under another version must be rebuilt)
+pub const PROTOCOL_VERSION: i32 = 5;
+
+/// Message Types
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    Send = 0,
+    Issuance = 20,
+    TransferOwnership = 21,
+    LockAddress = 30,
+    UnlockAddress = 31,
+    AddressOptions = 32,
+}
+
+impl MessageType {
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(MessageType::Send),
+            20 => Some(MessageType::Issuance),
+            21 => Some(MessageType::TransferOwnership),
+            30 => Some(MessageType::LockAddress),
+            31 => Some(MessageType::UnlockAddress),
+            32 => Some(MessageType::AddressOptions),
+            _ => None,
+        }
+    }
+
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// Decoded Messages
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Send(SendMessage),
+    Issuance(Issuance),
+    TransferOwnership(TransferOwnership),
+    LockAddress(LockAddress),
+    UnlockAddress(UnlockAddress),
+    AddressOptions(AddressOptions),
+}
+
+impl Message {
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            Message::Send(_) => MessageType::Send,
+            Message::Issuance(_) => MessageType::Issuance,
+            Message::TransferOwnership(_) => MessageType::TransferOwnership,
+            Message::LockAddress(_) => MessageType::LockAddress,
+            Message::UnlockAddress(_) => MessageType::UnlockAddress,
+            Message::AddressOptions(_) => MessageType::AddressOptions,
+        }
+    }
+
+    /// Envelope body (type id + payload), without the prefix
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = vec![self.message_type().id()];
+
+        match self {
+            Message::Send(send) => data.extend(send.encode()),
+            Message::Issuance(issuance) => data.extend(issuance.encode()),
+            Message::TransferOwnership(transfer) => data.extend(transfer.encode()),
+            Message::LockAddress(lock) => data.extend(lock.encode()),
+            Message::UnlockAddress(unlock) => data.extend(unlock.encode()),
+            Message::AddressOptions(options) => data.extend(options.encode()),
+        }
+
+        data
+    }
+}
+
+/// Decoding Errors
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    EmptyMessage,
+    UnknownMessageType(u8),
+    InvalidPayloadLength { expected: usize, actual: usize },
+    InvalidFlags(u8),
+    InvalidDivisibility(u8),
+    InvalidMemo(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::EmptyMessage => {
+                write!(f, "EmptyMessage: Missing message type")
+            }
+            DecodeError::UnknownMessageType(id) => {
+                write!(f, "UnknownMessageType: No message type with id {}", id)
+            }
+            DecodeError::InvalidPayloadLength { expected, actual } => write!(
+                f,
+                "InvalidPayloadLength: Expected {} bytes, found {}",
+                expected, actual
+            ),
+            DecodeError::InvalidFlags(flags) => {
+                write!(f, "InvalidFlags: Unknown flag bits in {:#010b}", flags)
+            }
+            DecodeError::InvalidDivisibility(divisibility) => {
+                write!(f, "InvalidDivisibility: {} decimals exceeds the maximum", divisibility)
+            }
+            DecodeError::InvalidMemo(reason) => write!(f, "InvalidMemo: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Envelope Extraction
+pub fn envelope(script: &Script) -> Option<Vec<u8>> {
+    if !script.is_op_return() {
+        return None;
+    }
+
+    // Concatenate every push following OP_RETURN
+    let mut data = vec![];
+    for instruction in script.instructions().skip(1) {
+        match instruction {
+            Ok(Instruction::PushBytes(bytes)) => data.extend_from_slice(bytes.as_bytes()),
+            _ => return None,
+        }
+    }
+
+    data.strip_prefix(PREFIX.as_slice()).map(|body| body.to_vec())
+}
+
+/// Decoding
+pub fn decode(data: &[u8]) -> Result<Message, DecodeError> {
+    let (&id, payload) = data.split_first().ok_or(DecodeError::EmptyMessage)?;
+
+    match MessageType::from_id(id) {
+        Some(MessageType::Send) => SendMessage::decode(payload).map(Message::Send),
+        Some(MessageType::Issuance) => Issuance::decode(payload).map(Message::Issuance),
+        Some(MessageType::TransferOwnership) => {
+            TransferOwnership::decode(payload).map(Message::TransferOwnership)
+        }
+        Some(MessageType::LockAddress) => LockAddress::decode(payload).map(Message::LockAddress),
+        Some(MessageType::UnlockAddress) => UnlockAddress::decode(payload).map(Message::UnlockAddress),
+        Some(MessageType::AddressOptions) => AddressOptions::decode(payload).map(Message::AddressOptions),
+        None => Err(DecodeError::UnknownMessageType(id)),
+    }
+}
+
+/// Decoding (first Artifact output only)
+pub fn decode_transaction(tx: &Transaction) -> Option<Result<Message, DecodeError>> {
+    tx.output
+        .iter()
+        .find_map(|output| envelope(&output.script_pubkey))
+        .map(|data| decode(&data))
+}
+
+/// Source Address
+///
+/// The address that signed the first input, recovered from the public key
+/// revealed when spending P2PKH, P2WPKH or P2SH-P2WPKH outputs. The key
+/// only counts if its address is the output being spent (`spent`), so a
+/// script that merely looks like a key spend can't pose as that key.
+/// Other spends (taproot, bare multisig, ...) carry no source.
+pub fn source_address(tx: &Transaction, spent: &Script, network: Network) -> Option<Address> {
+    key_spend_address(tx, network).filter(|address| address.script_pubkey().as_script() == spent)
+}
+
+/// Address of the key revealed by the first input, if it is shaped like
+/// a key spend
+fn key_spend_address(tx: &Transaction, network: Network) -> Option<Address> {
+    let input = tx.input.first()?;
+    if tx.is_coinbase() {
+        return None;
+    }
+
+    // P2WPKH (or P2SH-P2WPKH): [signature, pubkey]
+    if input.witness.len() == 2 {
+        let key = PublicKey::from_slice(input.witness.last()?).ok()?;
+
+        return if input.script_sig.is_empty() {
+            Address::p2wpkh(&key, network).ok()
+        } else {
+            Address::p2shwpkh(&key, network).ok()
+        };
+    }
+
+    // P2PKH: <signature> <pubkey>
+    if input.witness.is_empty() {
+        let pushes = input
+            .script_sig
+            .instructions()
+            .map(|i| i.ok()?.push_bytes().map(|b| b.as_bytes().to_vec()))
+            .collect::<Option<Vec<_>>>()?;
+
+        if let [_, key] = pushes.as_slice() {
+            return Some(Address::p2pkh(&PublicKey::from_slice(key).ok()?, network));
+        }
+    }
+
+    None
+}
+
+/// Destination Address
+///
+/// The first output paying a standard address, skipping data outputs.
+pub fn destination_address(tx: &Transaction, network: Network) -> Option<Address> {
+    tx.output
+        .iter()
+        .filter(|output| !output.script_pubkey.is_op_return())
+        .find_map(|output| Address::from_script(&output.script_pubkey, network).ok())
+}
+
+/// Encoding
+pub fn encode_script(message: &Message) -> bitcoin::ScriptBuf {
+    use bitcoin::script::PushBytesBuf;
+
+    let mut data = PREFIX.to_vec();
+    data.extend(message.encode());
+
+    let push = PushBytesBuf::try_from(data).expect("Artifact messages fit in a single push");
+    bitcoin::ScriptBuf::new_op_return(push)
+}
+
+/// Payload Length Check
+fn expect_length(payload: &[u8], expected: usize) -> Result<(), DecodeError> {
+    if payload.len() != expected {
+        Err(DecodeError::InvalidPayloadLength {
+            expected,
+            actual: payload.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::script::PushBytesBuf;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Amount, ScriptBuf, TxOut};
+
+    fn op_return(data: &[u8]) -> ScriptBuf {
+        ScriptBuf::new_op_return(PushBytesBuf::try_from(data.to_vec()).unwrap())
+    }
+
+    fn transaction(outputs: Vec<ScriptBuf>) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: outputs
+                .into_iter()
+                .map(|script_pubkey| TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn envelope_requires_op_return_and_prefix() {
+        // Case: Artifact Prefix
+        assert_eq!(envelope(&op_return(b"ART\x14")), Some(vec![20]));
+        // Case: Foreign Prefix
+        assert_eq!(envelope(&op_return(b"OMNI\x14")), None);
+        // Case: Not OP_RETURN
+        assert_eq!(envelope(&ScriptBuf::new()), None);
+        // Case: Prefix Only
+        assert_eq!(envelope(&op_return(b"ART")), Some(vec![]));
+    }
+
+    #[test]
+    fn envelope_joins_multiple_pushes() {
+        let script = bitcoin::script::Builder::new()
+            .push_opcode(bitcoin::opcodes::all::OP_RETURN)
+            .push_slice(b"AR")
+            .push_slice(b"T\x14")
+            .into_script();
+
+        // Case: Split Prefix
+        assert_eq!(envelope(&script), Some(vec![20]));
+    }
+
+    #[test]
+    fn decode_reports_rejection_reason() {
+        // Case: Empty
+        assert_eq!(decode(&[]), Err(DecodeError::EmptyMessage));
+        // Case: Unknown Type
+        assert_eq!(decode(&[255]), Err(DecodeError::UnknownMessageType(255)));
+        // Case: Short Payload
+        assert_eq!(
+            decode(&[20, 0, 0]),
+            Err(DecodeError::InvalidPayloadLength {
+                expected: 18,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn decode_round_trips_issuance() {
+        let message = Message::Issuance(Issuance {
+            token_id: 2966,
+            quantity: 1_000,
+            divisibility: 2,
+            flags: 3,
+        });
+
+        // Case: Round Trip
+        assert_eq!(decode(&message.encode()), Ok(message));
+    }
+
+    #[test]
+    fn decode_transaction_uses_first_artifact_output() {
+        let message = Message::Issuance(Issuance {
+            token_id: 2966,
+            quantity: 0,
+            divisibility: 0,
+            flags: 0,
+        });
+        let tx = transaction(vec![
+            ScriptBuf::new(),
+            op_return(b"OMNI"),
+            encode_script(&message),
+            op_return(b"ART\xff"),
+        ]);
+
+        // Case: Artifact Output
+        assert_eq!(decode_transaction(&tx), Some(Ok(message)));
+        // Case: No Artifact Output
+        assert_eq!(decode_transaction(&transaction(vec![ScriptBuf::new()])), None);
+    }
+
+    #[test]
+    fn source_address_from_first_input() {
+        let tx = fixtures::transaction(1, vec![]);
+        let spent = fixtures::address(1).script_pubkey();
+        let regtest = Address::p2wpkh(&fixtures::public_key(1), Network::Regtest).unwrap();
+
+        // Case: P2WPKH
+        assert_eq!(source_address(&tx, &spent, Network::Bitcoin), Some(fixtures::address(1)));
+        // Case: Network Prefix
+        assert_eq!(source_address(&tx, &regtest.script_pubkey(), Network::Regtest), Some(regtest));
+        // Case: No Inputs
+        assert_eq!(source_address(&transaction(vec![]), &spent, Network::Bitcoin), None);
+    }
+
+    #[test]
+    fn source_address_requires_matching_spent_output() {
+        let key = fixtures::public_key(1);
+        // Witness script that is just the key: [signature, <pubkey>]
+        let key_script = ScriptBuf::from_bytes(key.to_bytes());
+        let p2wsh = ScriptBuf::new_p2wsh(&key_script.wscript_hash());
+        let p2sh = ScriptBuf::new_p2sh(&key_script.script_hash());
+
+        let mut p2sh_spend = fixtures::transaction(1, vec![]);
+        p2sh_spend.input[0].witness = bitcoin::Witness::new();
+        p2sh_spend.input[0].script_sig = bitcoin::script::Builder::new()
+            .push_slice([0u8; 72])
+            .push_slice(PushBytesBuf::try_from(key.to_bytes()).unwrap())
+            .into_script();
+
+        // Case: P2WSH With Key-Shaped Witness
+        assert_eq!(source_address(&fixtures::transaction(1, vec![]), &p2wsh, Network::Bitcoin), None);
+        // Case: P2SH With Key-Shaped Redeem Script
+        assert_eq!(source_address(&p2sh_spend, &p2sh, Network::Bitcoin), None);
+        // Case: Genuine P2PKH
+        let p2pkh = Address::p2pkh(&key, Network::Bitcoin);
+        assert_eq!(source_address(&p2sh_spend, &p2pkh.script_pubkey(), Network::Bitcoin), Some(p2pkh));
+    }
+
+    #[test]
+    fn destination_address_skips_data_outputs() {
+        let tx = transaction(vec![
+            op_return(b"ART"),
+            ScriptBuf::new(),
+            fixtures::address(2).script_pubkey(),
+            fixtures::address(3).script_pubkey(),
+        ]);
+
+        // Case: First Standard Output
+        assert_eq!(destination_address(&tx, Network::Bitcoin), Some(fixtures::address(2)));
+        // Case: Data Only
+        assert_eq!(destination_address(&transaction(vec![op_return(b"ART")]), Network::Bitcoin), None);
+    }
+}
@@ -0,0 +1,105 @@
+use super::DecodeError;
+
+/// Payload Length (token id + quantity), before the optional memo
+pub const LENGTH: usize = 16;
+
+/// Maximum memo length in bytes (what fits in an 80 byte OP_RETURN)
+pub const MAX_MEMO_LENGTH: usize = 60;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SendMessage {
+    pub token_id: u64,
+    pub quantity: u64,
+    pub memo: Option<String>,
+}
+
+impl SendMessage {
+    pub fn decode(payload: &[u8]) -> Result<Self, DecodeError> {
+        if payload.len() < LENGTH {
+            return Err(DecodeError::InvalidPayloadLength {
+                expected: LENGTH,
+                actual: payload.len(),
+            });
+        }
+
+        let memo = match &payload[LENGTH..] {
+            [] => None,
+            memo => Some(decode_memo(memo)?),
+        };
+
+        Ok(Self {
+            token_id: u64::from_be_bytes(payload[0..8].try_into().unwrap()),
+            quantity: u64::from_be_bytes(payload[8..16].try_into().unwrap()),
+            memo,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut payload = self.token_id.to_be_bytes().to_vec();
+        payload.extend(self.quantity.to_be_bytes());
+        if let Some(memo) = &self.memo {
+            payload.extend(memo.as_bytes());
+        }
+        payload
+    }
+}
+
+/// Memo Decoding (UTF-8, no control characters, `MAX_MEMO_LENGTH` bytes)
+pub fn decode_memo(memo: &[u8]) -> Result<String, DecodeError> {
+    if memo.len() > MAX_MEMO_LENGTH {
+        return Err(DecodeError::InvalidMemo(format!(
+            "{} bytes, at most {} allowed",
+            memo.len(),
+            MAX_MEMO_LENGTH
+        )));
+    }
+
+    let memo = std::str::from_utf8(memo).map_err(|_| DecodeError::InvalidMemo("Must be UTF-8".to_string()))?;
+
+    if memo.chars().any(char::is_control) {
+        return Err(DecodeError::InvalidMemo("Control characters not allowed".to_string()));
+    }
+
+    Ok(memo.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_round_trips() {
+        let send = SendMessage {
+            token_id: 2966,
+            quantity: 10,
+            memo: None,
+        };
+
+        // Case: Round Trip
+        assert_eq!(SendMessage::decode(&send.encode()), Ok(send));
+        // Case: Control Character Memo
+        assert!(SendMessage::decode(&[0; 17]).is_err());
+    }
+
+    #[test]
+    fn decode_reads_memo() {
+        let send = SendMessage {
+            token_id: 2966,
+            quantity: 10,
+            memo: Some("Deposit 12345".to_string()),
+        };
+        let mut invalid_utf8 = send.encode();
+        invalid_utf8.push(0xff);
+
+        // Case: Round Trip
+        assert_eq!(SendMessage::decode(&send.encode()), Ok(send));
+        // Case: Invalid UTF-8
+        assert!(matches!(SendMessage::decode(&invalid_utf8), Err(DecodeError::InvalidMemo(_))));
+        // Case: Too Long
+        assert_eq!(
+            SendMessage::decode(&[b'A'; LENGTH + MAX_MEMO_LENGTH + 1]),
+            Err(DecodeError::InvalidMemo("61 bytes, at most 60 allowed".to_string()))
+        );
+        assert!(SendMessage::decode(&[b'A'; LENGTH + MAX_MEMO_LENGTH]).is_ok());
+    }
+}
@@ -0,0 +1,38 @@
+use super::{expect_length, DecodeError};
+
+/// Payload Length (token id)
+pub const LENGTH: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferOwnership {
+    pub token_id: u64,
+}
+
+impl TransferOwnership {
+    pub fn decode(payload: &[u8]) -> Result<Self, DecodeError> {
+        expect_length(payload, LENGTH)?;
+
+        Ok(Self {
+            token_id: u64::from_be_bytes(payload[0..8].try_into().unwrap()),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        self.token_id.to_be_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_round_trips() {
+        let transfer = TransferOwnership { token_id: 2966 };
+
+        // Case: Round Trip
+        assert_eq!(TransferOwnership::decode(&transfer.encode()), Ok(transfer));
+        // Case: Trailing Bytes
+        assert!(TransferOwnership::decode(&[0; 9]).is_err());
+    }
+}
@@ -1,9 +1,22 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    address_events (id) {
+        id -> Integer,
+        address -> Text,
+        event -> Text,
+        flags -> Integer,
+        unlock_height -> Nullable<Integer>,
+        block_height -> Integer,
+        txid -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     addresses (address) {
         address -> Text,
         flags -> Integer,
+        unlock_height -> Nullable<Integer>,
     }
 }
 
@@ -11,23 +24,106 @@ diesel::table! {
     balances (address, token) {
         address -> Text,
         token -> Text,
-        quantity -> Integer,
+        quantity -> BigInt,
     }
 }
 
 diesel::table! {
-    credits (address, token) {
+    blocks (height) {
+        height -> Integer,
+        hash -> Text,
+        prev_hash -> Text,
+        ledger_hash -> Text,
+    }
+}
+
+diesel::table! {
+    credits (id) {
+        id -> Integer,
         address -> Text,
         token -> Text,
-        quantity -> Integer,
+        quantity -> BigInt,
+        block_height -> Integer,
+        tx_index -> Integer,
+        txid -> Text,
+        event -> Text,
+        message_id -> Nullable<Integer>,
+        memo -> Nullable<Text>,
     }
 }
 
 diesel::table! {
-    debits (address, token) {
+    debits (id) {
+        id -> Integer,
         address -> Text,
         token -> Text,
-        quantity -> Integer,
+        quantity -> BigInt,
+        block_height -> Integer,
+        tx_index -> Integer,
+        txid -> Text,
+        event -> Text,
+        message_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    issuances (id) {
+        id -> Integer,
+        token -> Text,
+        issuer -> Text,
+        flags -> Integer,
+        block_height -> Integer,
+        txid -> Text,
+        quantity -> BigInt,
+        divisibility -> Integer,
+    }
+}
+
+diesel::table! {
+    ledger_info (id) {
+        id -> Integer,
+        schema_version -> Text,
+        protocol_version -> Integer,
+        network -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    messages (id) {
+        id -> Integer,
+        block_height -> Integer,
+        tx_index -> Integer,
+        txid -> Text,
+        source -> Text,
+        destination -> Nullable<Text>,
+        message_type -> Integer,
+        data -> Binary,
+    }
+}
+
+diesel::table! {
+    ownership_transfers (id) {
+        id -> Integer,
+        token -> Text,
+        source -> Text,
+        destination -> Text,
+        block_height -> Integer,
+        txid -> Text,
+    }
+}
+
+diesel::table! {
+    rejected_messages (id) {
+        id -> Integer,
+        block_height -> Integer,
+        tx_index -> Integer,
+        txid -> Text,
+        source -> Nullable<Text>,
+        destination -> Nullable<Text>,
+        message_type -> Nullable<Integer>,
+        reason_code -> Integer,
+        reason -> Text,
+        message_id -> Nullable<Integer>,
     }
 }
 
@@ -35,20 +131,40 @@ diesel::table! {
     tokens (token) {
         token -> Text,
         flags -> Integer,
+        divisibility -> Integer,
+        owner -> Nullable<Text>,
+        issuer -> Nullable<Text>,
+        issued_at_height -> Nullable<Integer>,
+        issuance_txid -> Nullable<Text>,
+        total_supply -> BigInt,
+        parent -> Nullable<Text>,
     }
 }
 
+diesel::joinable!(address_events -> addresses (address));
 diesel::joinable!(balances -> addresses (address));
 diesel::joinable!(balances -> tokens (token));
 diesel::joinable!(credits -> addresses (address));
+diesel::joinable!(credits -> messages (message_id));
 diesel::joinable!(credits -> tokens (token));
 diesel::joinable!(debits -> addresses (address));
+diesel::joinable!(debits -> messages (message_id));
 diesel::joinable!(debits -> tokens (token));
+diesel::joinable!(issuances -> tokens (token));
+diesel::joinable!(ownership_transfers -> tokens (token));
+diesel::joinable!(rejected_messages -> messages (message_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    address_events,
     addresses,
     balances,
+    blocks,
     credits,
     debits,
+    issuances,
+    ledger_info,
+    messages,
+    ownership_transfers,
+    rejected_messages,
     tokens,
 );
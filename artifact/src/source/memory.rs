@@ -0,0 +1,127 @@
+use crate::error::ArtifactError;
+use crate::source::{BlockSource, Tip};
+use bitcoin::block::Header;
+use bitcoin::{Block, BlockHash, OutPoint, ScriptBuf};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct State {
+    chain: Vec<BlockHash>,
+    /// Every block ever added, stale ones included
+    blocks: HashMap<BlockHash, Block>,
+    /// Outputs created outside the chain
+    funded: HashMap<OutPoint, ScriptBuf>,
+    failures: u32,
+}
+
+/// In-memory chain (tests and replaying synthetic chains)
+///
+/// Height 0 is the first block given, whatever its predecessor. Blocks can
+/// be added, reorganized and made to fail while an indexer reads them.
+#[derive(Default)]
+pub struct MemoryChain {
+    state: Mutex<State>,
+}
+
+impl MemoryChain {
+    pub fn new(blocks: Vec<Block>) -> Self {
+        let chain = Self::default();
+        chain.extend(blocks);
+        chain
+    }
+
+    /// Append `blocks` to the tip
+    pub fn extend(&self, blocks: Vec<Block>) {
+        let mut state = self.state.lock().unwrap();
+
+        for block in blocks {
+            let hash = block.block_hash();
+            state.chain.push(hash);
+            state.blocks.insert(hash, block);
+        }
+    }
+
+    /// Replace the blocks from `height` on with `blocks`
+    pub fn reorg(&self, height: u32, blocks: Vec<Block>) {
+        self.state.lock().unwrap().chain.truncate(height as usize);
+        self.extend(blocks);
+    }
+
+    /// Register an output created before the chain, spendable by its blocks
+    pub fn fund(&self, outpoint: OutPoint, script_pubkey: ScriptBuf) {
+        self.state.lock().unwrap().funded.insert(outpoint, script_pubkey);
+    }
+
+    /// Fail the next `count` requests, like a node going away
+    pub fn fail_next(&self, count: u32) {
+        self.state.lock().unwrap().failures = count;
+    }
+
+    /// Lock the state, failing if a failure is pending
+    fn state(&self) -> Result<std::sync::MutexGuard<'_, State>, ArtifactError> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.failures > 0 {
+            state.failures -= 1;
+            return Err(ArtifactError::Configuration("Memory chain unavailable".to_string()));
+        }
+
+        Ok(state)
+    }
+}
+
+impl BlockSource for MemoryChain {
+    fn tip(&self) -> Result<Tip, ArtifactError> {
+        let state = self.state()?;
+
+        match state.chain.last() {
+            Some(hash) => Ok(Tip {
+                height: state.chain.len() as u32 - 1,
+                hash: *hash,
+            }),
+            None => Err(ArtifactError::Configuration("Memory chain is empty".to_string())),
+        }
+    }
+
+    fn block_hash(&self, height: u32) -> Result<Option<BlockHash>, ArtifactError> {
+        Ok(self.state()?.chain.get(height as usize).copied())
+    }
+
+    fn block(&self, hash: &BlockHash) -> Result<Block, ArtifactError> {
+        self.state()?
+            .blocks
+            .get(hash)
+            .cloned()
+            .ok_or_else(|| ArtifactError::Configuration(format!("Unknown block {}", hash)))
+    }
+
+    fn header(&self, hash: &BlockHash) -> Result<Header, ArtifactError> {
+        self.block(hash).map(|block| block.header)
+    }
+
+    /// Outputs of funded outpoints and of any block seen, stale ones included
+    fn spent_scripts(&self, block: &Block) -> Result<HashMap<OutPoint, ScriptBuf>, ArtifactError> {
+        let state = self.state()?;
+        let mut spent = HashMap::new();
+
+        for input in block.txdata.iter().filter(|tx| !tx.is_coinbase()).flat_map(|tx| &tx.input) {
+            let outpoint = input.previous_output;
+            let script = state.funded.get(&outpoint).cloned().or_else(|| {
+                state
+                    .blocks
+                    .values()
+                    .flat_map(|block| &block.txdata)
+                    .find(|tx| tx.txid() == outpoint.txid)
+                    .and_then(|tx| tx.output.get(outpoint.vout as usize))
+                    .map(|output| output.script_pubkey.clone())
+            });
+
+            if let Some(script) = script {
+                spent.insert(outpoint, script);
+            }
+        }
+
+        Ok(spent)
+    }
+}
@@ -0,0 +1,160 @@
+pub mod memory;
+pub mod rest;
+
+use crate::error::ArtifactError;
+use bitcoin::block::Header;
+use bitcoin::{Block, BlockHash, OutPoint, ScriptBuf, Txid};
+use bitcoincore_rpc::jsonrpc::error::{Error as JsonRpcError, RpcError};
+use bitcoincore_rpc::{Client, RpcApi};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+pub use memory::MemoryChain;
+pub use rest::RestClient;
+
+/// Best block of a source's chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tip {
+    pub height: u32,
+    pub hash: BlockHash,
+}
+
+/// Block Source
+///
+/// Everything the indexer reads from the chain. `Client` is bitcoind's
+/// JSON-RPC, `RestClient` its REST interface, `BlockFileReader` its
+/// `blk*.dat` files and `MemoryChain` a synthetic chain for tests.
+pub trait BlockSource: Send + Sync {
+    fn tip(&self) -> Result<Tip, ArtifactError>;
+    /// Hash of the best-chain block at `height` (`None` past the tip)
+    fn block_hash(&self, height: u32) -> Result<Option<BlockHash>, ArtifactError>;
+    fn block(&self, hash: &BlockHash) -> Result<Block, ArtifactError>;
+    fn header(&self, hash: &BlockHash) -> Result<Header, ArtifactError>;
+
+    /// Script of every output spent by `block`'s inputs, which decides who
+    /// sent a message. Sources without undo data can't tell.
+    fn spent_scripts(&self, block: &Block) -> Result<HashMap<OutPoint, ScriptBuf>, ArtifactError> {
+        Err(ArtifactError::Configuration(format!(
+            "Spent outputs of block {} are not available from this source",
+            block.block_hash()
+        )))
+    }
+
+    /// Best-chain block at `height` (`None` past the tip)
+    fn block_at(&self, height: u32) -> Result<Option<Block>, ArtifactError> {
+        self.block_hash(height)?.map(|hash| self.block(&hash)).transpose()
+    }
+}
+
+/// Fail unless `source` reports the outputs spent by its tip block, so a
+/// node that can't (no undo data, before Bitcoin Core 23) is refused at
+/// startup rather than stalling at the first message
+pub fn check_spent_scripts(source: &dyn BlockSource) -> Result<(), ArtifactError> {
+    let tip = source.tip()?;
+    let block = source.block(&tip.hash)?;
+    let spent = source.spent_scripts(&block)?;
+
+    let unknown = block
+        .txdata
+        .iter()
+        .filter(|tx| !tx.is_coinbase())
+        .flat_map(|tx| &tx.input)
+        .any(|input| !spent.contains_key(&input.previous_output));
+    if unknown {
+        return Err(ArtifactError::Configuration(format!(
+            "Block source does not report the outputs spent by block {}",
+            tip.hash
+        )));
+    }
+
+    Ok(())
+}
+
+/// RPC error code for a height above the tip
+const RPC_INVALID_PARAMETER: i32 = -8;
+
+impl BlockSource for Client {
+    fn tip(&self) -> Result<Tip, ArtifactError> {
+        let info = self.get_blockchain_info()?;
+
+        Ok(Tip {
+            height: info.blocks as u32,
+            hash: info.best_block_hash,
+        })
+    }
+
+    fn block_hash(&self, height: u32) -> Result<Option<BlockHash>, ArtifactError> {
+        match self.get_block_hash(height.into()) {
+            Ok(hash) => Ok(Some(hash)),
+            Err(bitcoincore_rpc::Error::JsonRpc(JsonRpcError::Rpc(RpcError { code, .. })))
+                if code == RPC_INVALID_PARAMETER =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn block(&self, hash: &BlockHash) -> Result<Block, ArtifactError> {
+        Ok(self.get_block(hash)?)
+    }
+
+    fn header(&self, hash: &BlockHash) -> Result<Header, ArtifactError> {
+        Ok(self.get_block_header(hash)?)
+    }
+
+    /// `getblock` verbosity 3 (Bitcoin Core 23+, no `-txindex` needed)
+    fn spent_scripts(&self, block: &Block) -> Result<HashMap<OutPoint, ScriptBuf>, ArtifactError> {
+        let verbose: serde_json::Value = self.call("getblock", &[block.block_hash().to_string().into(), 3.into()])?;
+
+        parse_spent_scripts(&verbose)
+    }
+}
+
+/// Parsing (prevouts of a verbose block, as `getblock` verbosity 3 and
+/// the REST JSON block report them)
+pub(crate) fn parse_spent_scripts(block: &serde_json::Value) -> Result<HashMap<OutPoint, ScriptBuf>, ArtifactError> {
+    let invalid = |detail: &str| ArtifactError::Configuration(format!("Verbose block without prevouts: {}", detail));
+    let mut spent = HashMap::new();
+
+    let txs = block.get("tx").and_then(|txs| txs.as_array()).ok_or_else(|| invalid("no tx list"))?;
+    for tx in txs {
+        let inputs = tx.get("vin").and_then(|vin| vin.as_array()).ok_or_else(|| invalid("no vin list"))?;
+
+        // The coinbase spends nothing
+        for input in inputs.iter().filter(|input| input.get("coinbase").is_none()) {
+            let txid = input
+                .get("txid")
+                .and_then(|txid| txid.as_str())
+                .and_then(|txid| Txid::from_str(txid).ok())
+                .ok_or_else(|| invalid("input without txid"))?;
+            let vout = input.get("vout").and_then(|vout| vout.as_u64()).ok_or_else(|| invalid("input without vout"))?;
+            let script = input
+                .pointer("/prevout/scriptPubKey/hex")
+                .and_then(|hex| hex.as_str())
+                .and_then(|hex| ScriptBuf::from_hex(hex).ok())
+                .ok_or_else(|| invalid("input without prevout"))?;
+
+            spent.insert(OutPoint::new(txid, vout as u32), script);
+        }
+    }
+
+    Ok(spent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use bitcoin::hashes::Hash;
+
+    #[test]
+    fn check_spent_scripts_requires_prevouts() {
+        let spending = fixtures::block(BlockHash::all_zeros(), vec![fixtures::transaction(1, vec![])]);
+
+        // Case: Spent Outputs Known
+        check_spent_scripts(&fixtures::chain(vec![spending.clone()])).unwrap();
+        // Case: Spent Outputs Unknown
+        assert_eq!(check_spent_scripts(&MemoryChain::new(vec![spending])).unwrap_err().code(), 102);
+    }
+}
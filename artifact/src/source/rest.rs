@@ -0,0 +1,288 @@
+use crate::error::ArtifactError;
+use crate::source::{parse_spent_scripts, BlockSource, Tip};
+use bitcoin::block::Header;
+use bitcoin::consensus::deserialize;
+use bitcoin::{Block, BlockHash, Network, OutPoint, ScriptBuf};
+use std::collections::HashMap;
+use std::io::Read;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Chain state from `/rest/chaininfo.json`
+pub struct ChainInfo {
+    pub network: Network,
+    pub blocks: u64,
+    pub best_block_hash: BlockHash,
+}
+
+/// REST Client
+///
+/// Bitcoin Core's REST interface (`-rest`), served on the RPC port. Blocks
+/// come back as raw bytes instead of hex inside JSON, and no credentials
+/// are needed.
+pub struct RestClient {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl RestClient {
+    pub fn new(url: &str) -> Result<Self, ArtifactError> {
+        if !url.starts_with("http://") {
+            return Err(ArtifactError::Configuration(format!(
+                "REST URL must start with http://, got '{}'",
+                url
+            )));
+        }
+
+        Ok(Self {
+            base_url: url.trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(60)).build(),
+        })
+    }
+
+    pub fn chain_info(&self) -> Result<ChainInfo, ArtifactError> {
+        let body = self.get("chaininfo.json")?.ok_or_else(|| rest_error("chaininfo.json not found"))?;
+        let info: serde_json::Value =
+            serde_json::from_slice(&body).map_err(|e| rest_error(format!("chaininfo.json: {}", e)))?;
+
+        let field = |name: &str| info.get(name).ok_or_else(|| rest_error(format!("chaininfo.json lacks {}", name)));
+        let chain = field("chain")?.as_str().unwrap_or_default();
+
+        Ok(ChainInfo {
+            network: Network::from_core_arg(chain).map_err(|e| rest_error(e.to_string()))?,
+            blocks: field("blocks")?.as_u64().ok_or_else(|| rest_error("blocks is not a number"))?,
+            best_block_hash: BlockHash::from_str(field("bestblockhash")?.as_str().unwrap_or_default())
+                .map_err(|e| rest_error(format!("bestblockhash: {}", e)))?,
+        })
+    }
+
+    /// `GET /rest/<path>`, `None` on 404
+    fn get(&self, path: &str) -> Result<Option<Vec<u8>>, ArtifactError> {
+        let url = format!("{}/rest/{}", self.base_url, path);
+
+        let response = match self.agent.get(&url).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(ureq::Error::Status(status, response)) => {
+                let detail = response.into_string().unwrap_or_default();
+                return Err(rest_error(format!("{} returned {}: {}", url, status, detail.trim())));
+            }
+            Err(e) => return Err(rest_error(format!("{}: {}", url, e))),
+        };
+
+        let mut body = vec![];
+        response
+            .into_reader()
+            .read_to_end(&mut body)
+            .map_err(|e| rest_error(format!("{}: {}", url, e)))?;
+
+        Ok(Some(body))
+    }
+}
+
+impl BlockSource for RestClient {
+    fn tip(&self) -> Result<Tip, ArtifactError> {
+        let info = self.chain_info()?;
+
+        Ok(Tip {
+            height: info.blocks as u32,
+            hash: info.best_block_hash,
+        })
+    }
+
+    fn block_hash(&self, height: u32) -> Result<Option<BlockHash>, ArtifactError> {
+        self.get(&format!("blockhashbyheight/{}.bin", height))?
+            .map(|body| deserialize(&body).map_err(|e| rest_error(format!("block hash {}: {}", height, e))))
+            .transpose()
+    }
+
+    fn block(&self, hash: &BlockHash) -> Result<Block, ArtifactError> {
+        let body = self
+            .get(&format!("block/{}.bin", hash))?
+            .ok_or_else(|| rest_error(format!("block {} not found", hash)))?;
+
+        deserialize(&body).map_err(|e| rest_error(format!("block {}: {}", hash, e)))
+    }
+
+    /// Needs Bitcoin Core 24+ (`?count=`), unknown hashes get no headers
+    fn header(&self, hash: &BlockHash) -> Result<Header, ArtifactError> {
+        let body = self.get(&format!("headers/{}.bin?count=1", hash))?.unwrap_or_default();
+        if body.is_empty() {
+            return Err(rest_error(format!("header {} not found", hash)));
+        }
+
+        deserialize(&body).map_err(|e| rest_error(format!("header {}: {}", hash, e)))
+    }
+
+    /// The JSON block carries prevouts (Bitcoin Core 23+)
+    fn spent_scripts(&self, block: &Block) -> Result<HashMap<OutPoint, ScriptBuf>, ArtifactError> {
+        let hash = block.block_hash();
+        let body = self
+            .get(&format!("block/{}.json", hash))?
+            .ok_or_else(|| rest_error(format!("block {} not found", hash)))?;
+        let verbose: serde_json::Value =
+            serde_json::from_slice(&body).map_err(|e| rest_error(format!("block {}: {}", hash, e)))?;
+
+        parse_spent_scripts(&verbose)
+    }
+}
+
+fn rest_error(detail: impl Into<String>) -> ArtifactError {
+    ArtifactError::Rest(detail.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use bitcoin::consensus::serialize;
+    use bitcoin::hashes::Hash;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Local stand-in for bitcoind's REST interface serving `chain`
+    fn serve(chain: &[Block], network: &str) -> String {
+        let mut routes: HashMap<String, Vec<u8>> = HashMap::new();
+        for (height, block) in chain.iter().enumerate() {
+            routes.insert(format!("/rest/blockhashbyheight/{}.bin", height), serialize(&block.block_hash()));
+            routes.insert(format!("/rest/block/{}.bin", block.block_hash()), serialize(block));
+            routes.insert(format!("/rest/headers/{}.bin?count=1", block.block_hash()), serialize(&block.header));
+
+            // Verbose JSON carries the prevouts, here those of the test wallets
+            let spent = fixtures::spent_scripts(block);
+            let txs: Vec<serde_json::Value> = block
+                .txdata
+                .iter()
+                .map(|tx| {
+                    let inputs: Vec<serde_json::Value> = tx
+                        .input
+                        .iter()
+                        .map(|input| {
+                            serde_json::json!({
+                                "txid": input.previous_output.txid.to_string(),
+                                "vout": input.previous_output.vout,
+                                "prevout": {"scriptPubKey": {"hex": spent[&input.previous_output].to_hex_string()}},
+                            })
+                        })
+                        .collect();
+                    serde_json::json!({ "txid": tx.txid().to_string(), "vin": inputs })
+                })
+                .collect();
+            routes.insert(
+                format!("/rest/block/{}.json", block.block_hash()),
+                serde_json::json!({ "tx": txs }).to_string().into_bytes(),
+            );
+        }
+        let tip = chain.last().map_or(BlockHash::all_zeros(), |block| block.block_hash());
+        routes.insert(
+            "/rest/chaininfo.json".to_string(),
+            format!(
+                r#"{{"chain":"{}","blocks":{},"bestblockhash":"{}"}}"#,
+                network,
+                chain.len() as i64 - 1,
+                tip
+            )
+            .into_bytes(),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                // Drain the headers before answering
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap_or(0) > 2 {
+                    header.clear();
+                }
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+                let (status, body) = match routes.get(path) {
+                    Some(body) => ("200 OK", body.clone()),
+                    None => ("404 Not Found", b"Block height out of range".to_vec()),
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                let _ = stream.write_all(&body);
+            }
+        });
+
+        url
+    }
+
+    fn chain(length: u32) -> Vec<Block> {
+        let mut chain: Vec<Block> = vec![];
+        for time in 0..length {
+            let prev = chain.last().map_or(BlockHash::all_zeros(), |block| block.block_hash());
+            let mut block = fixtures::block(prev, vec![]);
+            block.header.time = time;
+            chain.push(block);
+        }
+        chain
+    }
+
+    #[test]
+    fn block_at_reads_binary_blocks() {
+        let chain = chain(3);
+        let client = RestClient::new(&serve(&chain, "regtest")).unwrap();
+
+        // Case: Block By Height
+        for (height, block) in chain.iter().enumerate() {
+            assert_eq!(client.block_at(height as u32).unwrap().as_ref(), Some(block));
+        }
+        // Case: Past The Tip
+        assert!(client.block_at(3).unwrap().is_none());
+        // Case: Header
+        assert_eq!(client.header(&chain[1].block_hash()).unwrap(), chain[1].header);
+        // Case: Unknown Hash
+        assert_eq!(client.block(&BlockHash::all_zeros()).unwrap_err().code(), 202);
+    }
+
+    #[test]
+    fn spent_scripts_reads_json_prevouts() {
+        let mut chain = chain(1);
+        let tx = fixtures::transaction(4, vec![]);
+        chain.push(fixtures::block(chain[0].block_hash(), vec![tx.clone()]));
+        let client = RestClient::new(&serve(&chain, "regtest")).unwrap();
+
+        let spent = client.spent_scripts(&chain[1]).unwrap();
+
+        // Case: Prevout Of Each Input
+        assert_eq!(spent.len(), 1);
+        assert_eq!(spent[&tx.input[0].previous_output], fixtures::address(4).script_pubkey());
+        // Case: Unknown Block
+        let unknown = fixtures::block(BlockHash::all_zeros(), vec![tx]);
+        assert_eq!(client.spent_scripts(&unknown).unwrap_err().code(), 202);
+    }
+
+    #[test]
+    fn chain_info_reports_network_and_tip() {
+        let chain = chain(2);
+        let client = RestClient::new(&format!("{}/", serve(&chain, "signet"))).unwrap();
+
+        let info = client.chain_info().unwrap();
+
+        // Case: Network And Tip
+        assert_eq!(info.network, Network::Signet);
+        assert_eq!(info.blocks, 1);
+        assert_eq!(
+            client.tip().unwrap(),
+            Tip {
+                height: 1,
+                hash: chain[1].block_hash()
+            }
+        );
+        // Case: Not HTTP
+        assert!(RestClient::new("localhost:8332").is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use super::LedgerStore;
+use crate::database::DbConnection;
+use crate::error::ArtifactError;
+use crate::models::address::{self, Address, Flags, NewAddressEvent};
+use crate::models::issuance::{self, NewIssuance};
+use crate::models::message::{Event, Origin};
+use crate::models::ownership::{self, NewOwnershipTransfer};
+use crate::models::token::{self, NewToken, Token};
+use crate::models::{balance, credit, debit};
+
+impl LedgerStore for DbConnection {
+    fn transaction<T, F>(&mut self, f: F) -> Result<T, ArtifactError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, ArtifactError>,
+    {
+        DbConnection::transaction(self, f)
+    }
+
+    fn fetch_token(&mut self, token: &str) -> Result<Option<Token>, ArtifactError> {
+        token::fetch_token(self, token)
+    }
+
+    fn create_token(&mut self, new_token: &NewToken) -> Result<(), ArtifactError> {
+        token::create_issued_token(self, new_token)
+    }
+
+    fn update_token(&mut self, token: &str, flags: &i32) -> Result<(), ArtifactError> {
+        token::update_token(self, token, flags)
+    }
+
+    fn update_supply(&mut self, token: &str, total_supply: &i64) -> Result<(), ArtifactError> {
+        token::update_supply(self, token, total_supply)
+    }
+
+    fn update_owner(&mut self, token: &str, owner: &str) -> Result<(), ArtifactError> {
+        token::update_owner(self, token, owner)
+    }
+
+    fn fetch_address(&mut self, address: &str) -> Result<Option<Address>, ArtifactError> {
+        address::fetch_address(self, address)
+    }
+
+    fn ensure_address(&mut self, address: &str) -> Result<(), ArtifactError> {
+        address::ensure_address(self, address)
+    }
+
+    fn update_flags(&mut self, address: &str, flags: Flags, unlock_height: Option<i32>) -> Result<(), ArtifactError> {
+        address::update_flags(self, address, flags, unlock_height)
+    }
+
+    fn fetch_quantity(&mut self, address: &str, token: &str) -> Result<i64, ArtifactError> {
+        balance::fetch_quantity(self, address, token)
+    }
+
+    fn upsert_balance(&mut self, address: &str, token: &str, quantity: &i64) -> Result<(), ArtifactError> {
+        balance::upsert_balance(self, address, token, quantity)
+    }
+
+    fn create_credit(
+        &mut self,
+        origin: &Origin,
+        event: Event,
+        address: &str,
+        token: &str,
+        quantity: &i64,
+        memo: Option<&str>,
+    ) -> Result<(), ArtifactError> {
+        credit::create_credit(self, origin, event, address, token, quantity, memo)
+    }
+
+    fn create_debit(
+        &mut self,
+        origin: &Origin,
+        event: Event,
+        address: &str,
+        token: &str,
+        quantity: &i64,
+    ) -> Result<(), ArtifactError> {
+        debit::create_debit(self, origin, event, address, token, quantity)
+    }
+
+    fn create_issuance(&mut self, new_issuance: &NewIssuance) -> Result<(), ArtifactError> {
+        issuance::create_issuance(self, new_issuance)
+    }
+
+    fn create_ownership_transfer(&mut self, new_transfer: &NewOwnershipTransfer) -> Result<(), ArtifactError> {
+        ownership::create_ownership_transfer(self, new_transfer)
+    }
+
+    fn create_address_event(&mut self, new_event: &NewAddressEvent) -> Result<(), ArtifactError> {
+        address::create_address_event(self, new_event)
+    }
+}
@@ -0,0 +1,406 @@
+use super::LedgerStore;
+use crate::error::ArtifactError;
+use crate::models::address::{Address, AddressEvent, Flags, NewAddressEvent};
+use crate::models::credit::Credit;
+use crate::models::debit::Debit;
+use crate::models::issuance::{Issuance, NewIssuance};
+use crate::models::message::{Event, Origin};
+use crate::models::ownership::{NewOwnershipTransfer, OwnershipTransfer};
+use crate::models::token::{NewToken, Token};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// In-memory ledger (tests and embedders without a database)
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    pub tokens: HashMap<String, Token>,
+    pub addresses: HashMap<String, Address>,
+    pub balances: HashMap<(String, String), i64>,
+    pub credits: Vec<Credit>,
+    pub debits: Vec<Debit>,
+    pub issuances: Vec<Issuance>,
+    pub ownership_transfers: Vec<OwnershipTransfer>,
+    pub address_events: Vec<AddressEvent>,
+    /// Open transactions
+    depth: usize,
+    /// How to take back each change made inside them, oldest first
+    undo_log: Vec<Undo>,
+}
+
+/// A change made inside a transaction, and what it replaced
+#[derive(Clone)]
+enum Undo {
+    Token(String, Option<Token>),
+    Address(String, Option<Address>),
+    Balance((String, String), Option<i64>),
+    Credit,
+    Debit,
+    Issuance,
+    OwnershipTransfer,
+    AddressEvent,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember how to undo a change, when a transaction may need to
+    fn record(&mut self, undo: Undo) {
+        if self.depth > 0 {
+            self.undo_log.push(undo);
+        }
+    }
+
+    /// Take back every change made since the log was `len` long
+    fn undo_to(&mut self, len: usize) {
+        for undo in self.undo_log.split_off(len).into_iter().rev() {
+            match undo {
+                Undo::Token(key, previous) => restore(&mut self.tokens, key, previous),
+                Undo::Address(key, previous) => restore(&mut self.addresses, key, previous),
+                Undo::Balance(key, previous) => restore(&mut self.balances, key, previous),
+                Undo::Credit => {
+                    self.credits.pop();
+                }
+                Undo::Debit => {
+                    self.debits.pop();
+                }
+                Undo::Issuance => {
+                    self.issuances.pop();
+                }
+                Undo::OwnershipTransfer => {
+                    self.ownership_transfers.pop();
+                }
+                Undo::AddressEvent => {
+                    self.address_events.pop();
+                }
+            }
+        }
+    }
+
+    fn token_mut(&mut self, token: &str) -> Result<&mut Token, ArtifactError> {
+        let current = self
+            .tokens
+            .get(token)
+            .cloned()
+            .ok_or_else(|| ArtifactError::NotFound(format!("Token {}", token)))?;
+        self.record(Undo::Token(token.to_string(), Some(current)));
+
+        Ok(self.tokens.get_mut(token).expect("token was just found"))
+    }
+}
+
+/// Put back what `key` held before (or nothing)
+fn restore<K: Eq + Hash, V>(map: &mut HashMap<K, V>, key: K, previous: Option<V>) {
+    match previous {
+        Some(value) => {
+            map.insert(key, value);
+        }
+        None => {
+            map.remove(&key);
+        }
+    }
+}
+
+impl LedgerStore for MemoryStore {
+    fn transaction<T, F>(&mut self, f: F) -> Result<T, ArtifactError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, ArtifactError>,
+    {
+        // Undo back to here on failure, nesting gives savepoints
+        let savepoint = self.undo_log.len();
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+
+        if result.is_err() {
+            self.undo_to(savepoint);
+        } else if self.depth == 0 {
+            self.undo_log.clear();
+        }
+
+        result
+    }
+
+    fn fetch_token(&mut self, token: &str) -> Result<Option<Token>, ArtifactError> {
+        Ok(self.tokens.get(token).cloned())
+    }
+
+    fn create_token(&mut self, new_token: &NewToken) -> Result<(), ArtifactError> {
+        if self.tokens.contains_key(new_token.token) {
+            return Err(ArtifactError::AlreadyExists(format!("Token {}", new_token.token)));
+        }
+
+        let token = Token {
+            token: new_token.token.to_string(),
+            flags: *new_token.flags,
+            divisibility: *new_token.divisibility,
+            owner: new_token.owner.map(str::to_string),
+            issuer: new_token.issuer.map(str::to_string),
+            issued_at_height: new_token.issued_at_height.copied(),
+            issuance_txid: new_token.issuance_txid.map(str::to_string),
+            total_supply: *new_token.total_supply,
+            parent: new_token.parent.map(str::to_string),
+        };
+
+        self.tokens.insert(token.token.clone(), token);
+        self.record(Undo::Token(new_token.token.to_string(), None));
+        Ok(())
+    }
+
+    fn update_token(&mut self, token: &str, flags: &i32) -> Result<(), ArtifactError> {
+        self.token_mut(token)?.flags = *flags;
+        Ok(())
+    }
+
+    fn update_supply(&mut self, token: &str, total_supply: &i64) -> Result<(), ArtifactError> {
+        self.token_mut(token)?.total_supply = *total_supply;
+        Ok(())
+    }
+
+    fn update_owner(&mut self, token: &str, owner: &str) -> Result<(), ArtifactError> {
+        self.token_mut(token)?.owner = Some(owner.to_string());
+        Ok(())
+    }
+
+    fn fetch_address(&mut self, address: &str) -> Result<Option<Address>, ArtifactError> {
+        Ok(self.addresses.get(address).cloned())
+    }
+
+    fn ensure_address(&mut self, address: &str) -> Result<(), ArtifactError> {
+        if !self.addresses.contains_key(address) {
+            self.record(Undo::Address(address.to_string(), None));
+            self.addresses.insert(
+                address.to_string(),
+                Address {
+                    address: address.to_string(),
+                    flags: 0,
+                    unlock_height: None,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    fn update_flags(&mut self, address: &str, flags: Flags, unlock_height: Option<i32>) -> Result<(), ArtifactError> {
+        if let Some(current) = self.addresses.get(address).cloned() {
+            self.record(Undo::Address(address.to_string(), Some(current)));
+        }
+        if let Some(current) = self.addresses.get_mut(address) {
+            current.flags = flags.bits();
+            current.unlock_height = unlock_height;
+        }
+
+        Ok(())
+    }
+
+    fn fetch_quantity(&mut self, address: &str, token: &str) -> Result<i64, ArtifactError> {
+        Ok(self
+            .balances
+            .get(&(address.to_string(), token.to_string()))
+            .copied()
+            .unwrap_or(0))
+    }
+
+    fn upsert_balance(&mut self, address: &str, token: &str, quantity: &i64) -> Result<(), ArtifactError> {
+        let key = (address.to_string(), token.to_string());
+        let replaced = self.balances.insert(key.clone(), *quantity);
+        self.record(Undo::Balance(key, replaced));
+        Ok(())
+    }
+
+    fn create_credit(
+        &mut self,
+        origin: &Origin,
+        event: Event,
+        address: &str,
+        token: &str,
+        quantity: &i64,
+        memo: Option<&str>,
+    ) -> Result<(), ArtifactError> {
+        self.credits.push(Credit {
+            id: self.credits.len() as i32 + 1,
+            address: address.to_string(),
+            token: token.to_string(),
+            quantity: *quantity,
+            block_height: origin.block_height,
+            tx_index: origin.tx_index,
+            txid: origin.txid.to_string(),
+            event: event.as_str().to_string(),
+            message_id: origin.message_id,
+            memo: memo.map(str::to_string),
+        });
+        self.record(Undo::Credit);
+
+        Ok(())
+    }
+
+    fn create_debit(
+        &mut self,
+        origin: &Origin,
+        event: Event,
+        address: &str,
+        token: &str,
+        quantity: &i64,
+    ) -> Result<(), ArtifactError> {
+        self.debits.push(Debit {
+            id: self.debits.len() as i32 + 1,
+            address: address.to_string(),
+            token: token.to_string(),
+            quantity: *quantity,
+            block_height: origin.block_height,
+            tx_index: origin.tx_index,
+            txid: origin.txid.to_string(),
+            event: event.as_str().to_string(),
+            message_id: origin.message_id,
+        });
+        self.record(Undo::Debit);
+
+        Ok(())
+    }
+
+    fn create_issuance(&mut self, new_issuance: &NewIssuance) -> Result<(), ArtifactError> {
+        self.issuances.push(Issuance {
+            id: self.issuances.len() as i32 + 1,
+            token: new_issuance.token.to_string(),
+            issuer: new_issuance.issuer.to_string(),
+            flags: *new_issuance.flags,
+            block_height: *new_issuance.block_height,
+            txid: new_issuance.txid.to_string(),
+            quantity: *new_issuance.quantity,
+            divisibility: *new_issuance.divisibility,
+        });
+        self.record(Undo::Issuance);
+
+        Ok(())
+    }
+
+    fn create_ownership_transfer(&mut self, new_transfer: &NewOwnershipTransfer) -> Result<(), ArtifactError> {
+        self.ownership_transfers.push(OwnershipTransfer {
+            id: self.ownership_transfers.len() as i32 + 1,
+            token: new_transfer.token.to_string(),
+            source: new_transfer.source.to_string(),
+            destination: new_transfer.destination.to_string(),
+            block_height: *new_transfer.block_height,
+            txid: new_transfer.txid.to_string(),
+        });
+        self.record(Undo::OwnershipTransfer);
+
+        Ok(())
+    }
+
+    fn create_address_event(&mut self, new_event: &NewAddressEvent) -> Result<(), ArtifactError> {
+        self.address_events.push(AddressEvent {
+            id: self.address_events.len() as i32 + 1,
+            address: new_event.address.to_string(),
+            event: new_event.event.to_string(),
+            flags: *new_event.flags,
+            unlock_height: new_event.unlock_height.copied(),
+            block_height: *new_event.block_height,
+            txid: new_event.txid.map(str::to_string),
+        });
+        self.record(Undo::AddressEvent);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Reason;
+    use crate::models::{ownership, send, token};
+
+    const ORIGIN: Origin = Origin {
+        block_height: 1,
+        tx_index: 0,
+        txid: "txid",
+        message_id: Some(1),
+    };
+
+    #[test]
+    fn savepoint_rolls_back_inner_only() {
+        let mut store = MemoryStore::new();
+
+        store
+            .transaction(|store| {
+                store.ensure_address("alice")?;
+                let inner = store.transaction(|store| {
+                    store.ensure_address("bob")?;
+                    Err::<(), _>(ArtifactError::rejected(Reason::InvalidAddress, "bob"))
+                });
+
+                // Case: Inner Failure Contained
+                assert!(inner.is_err());
+                Ok(())
+            })
+            .unwrap();
+
+        // Case: Outer Committed
+        assert!(store.addresses.contains_key("alice"));
+        // Case: Savepoint Released
+        assert!(!store.addresses.contains_key("bob"));
+    }
+
+    #[test]
+    fn failed_transaction_undoes_every_change() {
+        let mut store = MemoryStore::new();
+        let id = token::generate_id("MEM").unwrap();
+        token::parse(&mut store, &ORIGIN, "alice", id, 100, 0, 0).unwrap();
+
+        let failed = store.transaction(|store| {
+            send::parse(store, &ORIGIN, "alice", "bob", id, 40, None)?;
+            ownership::parse(store, &ORIGIN, "alice", "bob", id)?;
+            store.update_supply("MEM", &1)?;
+            Err::<(), _>(ArtifactError::rejected(Reason::InvalidQuantity, "abort"))
+        });
+
+        // Case: Balances, Journal And Token Restored
+        assert!(failed.is_err());
+        assert_eq!(store.fetch_quantity("alice", "MEM").unwrap(), 100);
+        assert!(!store.balances.contains_key(&("bob".to_string(), "MEM".to_string())));
+        assert!(!store.addresses.contains_key("bob"));
+        assert_eq!((store.credits.len(), store.debits.len(), store.ownership_transfers.len()), (1, 0, 0));
+        assert_eq!(store.tokens["MEM"].owner.as_deref(), Some("alice"));
+        assert_eq!(store.tokens["MEM"].total_supply, 100);
+        // Case: Log Cleared Once Committed
+        assert!(store.undo_log.is_empty());
+    }
+
+    #[test]
+    fn missing_token_is_not_found() {
+        let mut store = MemoryStore::new();
+
+        // Case: Store-Neutral Error
+        let error = store.update_owner("NOPE", "bob").unwrap_err();
+        assert!(matches!(error, ArtifactError::NotFound(_)));
+        assert_eq!(error.code(), 104);
+        // Case: Missing Lookups Are None
+        assert!(store.fetch_token("NOPE").unwrap().is_none());
+    }
+
+    #[test]
+    fn consensus_runs_without_a_database() {
+        let mut store = MemoryStore::new();
+        let id = token::generate_id("MEM").unwrap();
+
+        token::parse(&mut store, &ORIGIN, "alice", id, 100, 0, 0).unwrap();
+        send::parse(&mut store, &ORIGIN, "alice", "bob", id, 40, None).unwrap();
+        ownership::parse(&mut store, &ORIGIN, "alice", "bob", id).unwrap();
+
+        // Case: Balances Moved
+        assert_eq!(store.fetch_quantity("alice", "MEM").unwrap(), 60);
+        assert_eq!(store.fetch_quantity("bob", "MEM").unwrap(), 40);
+        // Case: Journaled
+        assert_eq!((store.credits.len(), store.debits.len()), (2, 1));
+        // Case: Ownership Moved
+        assert_eq!(store.tokens["MEM"].owner.as_deref(), Some("bob"));
+        // Case: Rejection Leaves Store Untouched
+        assert_eq!(
+            send::parse(&mut store, &ORIGIN, "bob", "alice", id, 41, None).unwrap_err().reason(),
+            Some(Reason::InsufficientBalance)
+        );
+        assert_eq!(store.fetch_quantity("bob", "MEM").unwrap(), 40);
+    }
+}
@@ -0,0 +1,103 @@
+pub mod database;
+pub mod memory;
+
+use crate::error::ArtifactError;
+use crate::models::address::{Address, Flags, NewAddressEvent};
+use crate::models::issuance::NewIssuance;
+use crate::models::message::{Event, Origin};
+use crate::models::ownership::NewOwnershipTransfer;
+use crate::models::token::{NewToken, Token};
+
+pub use memory::MemoryStore;
+
+/// Ledger Storage
+///
+/// Everything the protocol rules read and write: tokens, addresses,
+/// balances and the credit/debit journal. `DbConnection` is the
+/// indexer's store, `MemoryStore` keeps the ledger in `HashMap`s.
+pub trait LedgerStore {
+    /// Run `f` atomically, a nested call is a savepoint
+    fn transaction<T, F>(&mut self, f: F) -> Result<T, ArtifactError>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> Result<T, ArtifactError>;
+
+    // Tokens
+    fn fetch_token(&mut self, token: &str) -> Result<Option<Token>, ArtifactError>;
+    fn create_token(&mut self, new_token: &NewToken) -> Result<(), ArtifactError>;
+    fn update_token(&mut self, token: &str, flags: &i32) -> Result<(), ArtifactError>;
+    fn update_supply(&mut self, token: &str, total_supply: &i64) -> Result<(), ArtifactError>;
+    fn update_owner(&mut self, token: &str, owner: &str) -> Result<(), ArtifactError>;
+
+    // Addresses
+    fn fetch_address(&mut self, address: &str) -> Result<Option<Address>, ArtifactError>;
+    fn ensure_address(&mut self, address: &str) -> Result<(), ArtifactError>;
+    fn update_flags(&mut self, address: &str, flags: Flags, unlock_height: Option<i32>) -> Result<(), ArtifactError>;
+
+    // Balances
+    fn fetch_quantity(&mut self, address: &str, token: &str) -> Result<i64, ArtifactError>;
+    fn upsert_balance(&mut self, address: &str, token: &str, quantity: &i64) -> Result<(), ArtifactError>;
+
+    // Journal
+    fn create_credit(
+        &mut self,
+        origin: &Origin,
+        event: Event,
+        address: &str,
+        token: &str,
+        quantity: &i64,
+        memo: Option<&str>,
+    ) -> Result<(), ArtifactError>;
+    fn create_debit(
+        &mut self,
+        origin: &Origin,
+        event: Event,
+        address: &str,
+        token: &str,
+        quantity: &i64,
+    ) -> Result<(), ArtifactError>;
+    fn create_issuance(&mut self, new_issuance: &NewIssuance) -> Result<(), ArtifactError>;
+    fn create_ownership_transfer(&mut self, new_transfer: &NewOwnershipTransfer) -> Result<(), ArtifactError>;
+    fn create_address_event(&mut self, new_event: &NewAddressEvent) -> Result<(), ArtifactError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::establish_test_connection;
+
+    /// Token errors every store must report alike
+    fn check_token_errors<S: LedgerStore>(store: &mut S) {
+        let new_token = NewToken {
+            token: "DUP",
+            flags: &0,
+            divisibility: &0,
+            owner: Some("alice"),
+            issuer: Some("alice"),
+            issued_at_height: Some(&1),
+            issuance_txid: Some("txid"),
+            total_supply: &100,
+            parent: None,
+        };
+        store.create_token(&new_token).unwrap();
+
+        // Case: Duplicate Refused (in a savepoint, Postgres aborts the rest)
+        let error = store
+            .transaction(|store| store.create_token(&NewToken { owner: Some("bob"), ..new_token }))
+            .unwrap_err();
+        assert!(matches!(error, ArtifactError::AlreadyExists(_)));
+        assert_eq!(store.fetch_token("DUP").unwrap().unwrap().owner.as_deref(), Some("alice"));
+        // Case: Missing Token
+        assert_eq!(store.transaction(|store| store.update_supply("NOPE", &1)).unwrap_err().code(), 104);
+    }
+
+    #[test]
+    fn stores_agree_on_token_errors() {
+        check_token_errors(&mut MemoryStore::new());
+        check_token_errors(&mut establish_test_connection());
+        #[cfg(feature = "postgres")]
+        if let Some(mut postgres) = crate::fixtures::establish_test_postgres_connection() {
+            check_token_errors(&mut postgres);
+        }
+    }
+}